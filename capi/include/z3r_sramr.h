@@ -0,0 +1,160 @@
+#ifndef Z3R_SRAMR_H
+#define Z3R_SRAMR_H
+
+/* Generated by cbindgen from capi/src/lib.rs. Don't edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Returned by every function that can fail.
+ */
+typedef enum Z3rError {
+  Z3R_ERROR_OK = 0,
+  /**
+   * A required pointer argument was null.
+   */
+  Z3R_ERROR_NULL_POINTER,
+  Z3R_ERROR_INVALID_SIZE,
+  Z3R_ERROR_INVALID_FILE,
+  Z3R_ERROR_INVALID_ROM_NAME,
+  Z3R_ERROR_INVALID_CHECKSUM,
+  /**
+   * The file passed validation, or wasn't validated, but couldn't be parsed.
+   */
+  Z3R_ERROR_PARSE_FAILED,
+  Z3R_ERROR_UNKNOWN_FIELD,
+  /**
+   * The field is text, such as a meta value, and has no number.
+   */
+  Z3R_ERROR_NOT_A_NUMBER,
+  /**
+   * The field's text and terminating NUL don't fit in the buffer.
+   */
+  Z3R_ERROR_BUFFER_TOO_SMALL,
+  /**
+   * A field name wasn't valid UTF-8.
+   */
+  Z3R_ERROR_INVALID_NAME,
+  /**
+   * The library panicked. This is a bug; builds that abort on panic never
+   * return it.
+   */
+  Z3R_ERROR_PANIC,
+} Z3rError;
+
+/**
+ * Stats that can be read without a name. Times are in frames.
+ */
+typedef enum Z3rStat {
+  Z3R_STAT_TOTAL_TIME,
+  Z3R_STAT_LAG_TIME,
+  Z3R_STAT_MENU_TIME,
+  Z3R_STAT_FIRST_SWORD,
+  Z3R_STAT_BOOTS_FOUND,
+  Z3R_STAT_FLUTE_FOUND,
+  Z3R_STAT_MIRROR_FOUND,
+  Z3R_STAT_COLLECTION_RATE,
+  Z3R_STAT_CHEST_LOCATIONS,
+  Z3R_STAT_OTHER_LOCATIONS,
+  Z3R_STAT_DEATHS,
+  Z3R_STAT_FAERIE_REVIVALS,
+  Z3R_STAT_SAVE_AND_QUITS,
+  Z3R_STAT_BONKS,
+} Z3rStat;
+
+/**
+ * A parsed file. Create with `z3r_parse` and release with `z3r_free`.
+ */
+typedef struct Z3rSram Z3rSram;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Checks that `sram` is a valid randomizer save.
+ *
+ * # Safety
+ *
+ * `sram` must point to `len` readable bytes.
+ */
+enum Z3rError z3r_validate(const uint8_t *sram, size_t len);
+
+/**
+ * Parses `sram` and stores a new handle in `out`. `out` is left untouched
+ * on failure.
+ *
+ * # Safety
+ *
+ * `sram` must point to `len` readable bytes and `out` must be writable.
+ */
+enum Z3rError z3r_parse(const uint8_t *sram, size_t len, bool validate, struct Z3rSram **out);
+
+/**
+ * Releases a handle from `z3r_parse`. Passing null does nothing.
+ *
+ * # Safety
+ *
+ * `sram` must be null or a handle from `z3r_parse` that hasn't been freed.
+ */
+void z3r_free(struct Z3rSram *sram);
+
+/**
+ * Reads a stat as a number. `stat` is a `Z3rStat` and anything else
+ * returns `Z3R_ERROR_UNKNOWN_FIELD`. Times are in frames and fractions give
+ * their numerator.
+ *
+ * # Safety
+ *
+ * `sram` must be a live handle and `out` must be writable.
+ */
+enum Z3rError z3r_stat(const struct Z3rSram *sram, uint32_t stat, uint32_t *out);
+
+/**
+ * Like `z3r_stat` but takes a `read_stats` name such as `"small keys"`.
+ *
+ * # Safety
+ *
+ * `sram` must be a live handle, `name` a NUL-terminated string and `out`
+ * must be writable.
+ */
+enum Z3rError z3r_stat_by_name(const struct Z3rSram *sram, const char *name, uint32_t *out);
+
+/**
+ * Writes a stat's text, e.g. `00:12:34.56` for times, to `buf` with a
+ * terminating NUL. This is the only way to read meta values such as
+ * `"hash id"`, which read as `none` when the file doesn't have them.
+ *
+ * # Safety
+ *
+ * `sram` must be a live handle, `name` a NUL-terminated string and `buf`
+ * must have `len` writable bytes.
+ */
+enum Z3rError z3r_stat_text(const struct Z3rSram *sram, const char *name, char *buf, size_t len);
+
+/**
+ * Reads an equipment value by its `read_equipment` name. Items you either
+ * have or don't read as 0 or 1.
+ *
+ * # Safety
+ *
+ * `sram` must be a live handle, `name` a NUL-terminated string and `out`
+ * must be writable.
+ */
+enum Z3rError z3r_equipment(const struct Z3rSram *sram, const char *name, uint32_t *out);
+
+/**
+ * A static, NUL-terminated description of an error code. `error` is a
+ * `Z3rError` and anything else is described as an unknown error.
+ */
+const char *z3r_error_message(uint32_t error);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* Z3R_SRAMR_H */
@@ -0,0 +1,342 @@
+use std::{
+    convert::TryFrom,
+    ffi::CStr,
+    os::raw::c_char,
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use z3r_sramr::{equipment::Z3REquip, error::Error, parse_sram, ParsedSram};
+
+/// Returned by every function that can fail.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Z3rError {
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer,
+    InvalidSize,
+    InvalidFile,
+    InvalidRomName,
+    InvalidChecksum,
+    /// The file passed validation, or wasn't validated, but couldn't be parsed.
+    ParseFailed,
+    UnknownField,
+    /// The field is text, such as a meta value, and has no number.
+    NotANumber,
+    /// The field's text and terminating NUL don't fit in the buffer.
+    BufferTooSmall,
+    /// A field name wasn't valid UTF-8.
+    InvalidName,
+    /// The library panicked. This is a bug; builds that abort on panic never
+    /// return it.
+    Panic,
+}
+
+impl Z3rError {
+    // Error codes come back from C as integers too, see `Z3rStat::from_u32`
+    fn from_u32(error: u32) -> Option<Self> {
+        let error = match error {
+            0 => Self::Ok,
+            1 => Self::NullPointer,
+            2 => Self::InvalidSize,
+            3 => Self::InvalidFile,
+            4 => Self::InvalidRomName,
+            5 => Self::InvalidChecksum,
+            6 => Self::ParseFailed,
+            7 => Self::UnknownField,
+            8 => Self::NotANumber,
+            9 => Self::BufferTooSmall,
+            10 => Self::InvalidName,
+            11 => Self::Panic,
+            _ => return None,
+        };
+
+        Some(error)
+    }
+}
+
+impl From<&Error> for Z3rError {
+    fn from(e: &Error) -> Self {
+        match e {
+            Error::InvalidSize => Self::InvalidSize,
+            Error::InvalidFile => Self::InvalidFile,
+            Error::InvalidRomName => Self::InvalidRomName,
+            Error::InvalidChecksum => Self::InvalidChecksum,
+            _ => Self::ParseFailed,
+        }
+    }
+}
+
+/// Stats that can be read without a name. Times are in frames.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Z3rStat {
+    TotalTime,
+    LagTime,
+    MenuTime,
+    FirstSword,
+    BootsFound,
+    FluteFound,
+    MirrorFound,
+    CollectionRate,
+    ChestLocations,
+    OtherLocations,
+    Deaths,
+    FaerieRevivals,
+    SaveAndQuits,
+    Bonks,
+}
+
+impl Z3rStat {
+    // C can pass any integer where an enum is expected, so stats are taken as
+    // integers and checked here
+    fn from_u32(stat: u32) -> Option<Self> {
+        let stat = match stat {
+            0 => Self::TotalTime,
+            1 => Self::LagTime,
+            2 => Self::MenuTime,
+            3 => Self::FirstSword,
+            4 => Self::BootsFound,
+            5 => Self::FluteFound,
+            6 => Self::MirrorFound,
+            7 => Self::CollectionRate,
+            8 => Self::ChestLocations,
+            9 => Self::OtherLocations,
+            10 => Self::Deaths,
+            11 => Self::FaerieRevivals,
+            12 => Self::SaveAndQuits,
+            13 => Self::Bonks,
+            _ => return None,
+        };
+
+        Some(stat)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::TotalTime => "total time",
+            Self::LagTime => "lag time",
+            Self::MenuTime => "menu time",
+            Self::FirstSword => "first sword",
+            Self::BootsFound => "boots found",
+            Self::FluteFound => "flute found",
+            Self::MirrorFound => "mirror found",
+            Self::CollectionRate => "collection rate",
+            Self::ChestLocations => "chest locations",
+            Self::OtherLocations => "other locations",
+            Self::Deaths => "deaths",
+            Self::FaerieRevivals => "faerie revivals",
+            Self::SaveAndQuits => "save and quits",
+            Self::Bonks => "bonks",
+        }
+    }
+}
+
+/// A parsed file. Create with `z3r_parse` and release with `z3r_free`.
+pub struct Z3rSram {
+    parsed: ParsedSram,
+}
+
+/// Checks that `sram` is a valid randomizer save.
+///
+/// # Safety
+///
+/// `sram` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn z3r_validate(sram: *const u8, len: usize) -> Z3rError {
+    if sram.is_null() {
+        return Z3rError::NullPointer;
+    }
+    let sram = slice::from_raw_parts(sram, len);
+    guard(|| match z3r_sramr::validate_sram(sram) {
+        Ok(()) => Z3rError::Ok,
+        Err(e) => Z3rError::from(&e),
+    })
+}
+
+/// Parses `sram` and stores a new handle in `out`. `out` is left untouched
+/// on failure.
+///
+/// # Safety
+///
+/// `sram` must point to `len` readable bytes and `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn z3r_parse(
+    sram: *const u8,
+    len: usize,
+    validate: bool,
+    out: *mut *mut Z3rSram,
+) -> Z3rError {
+    if sram.is_null() || out.is_null() {
+        return Z3rError::NullPointer;
+    }
+    let sram = slice::from_raw_parts(sram, len);
+    guard(|| match parse_sram(sram, validate) {
+        Ok(parsed) => {
+            *out = Box::into_raw(Box::new(Z3rSram { parsed }));
+            Z3rError::Ok
+        }
+        Err(e) => Z3rError::from(&e),
+    })
+}
+
+/// Releases a handle from `z3r_parse`. Passing null does nothing.
+///
+/// # Safety
+///
+/// `sram` must be null or a handle from `z3r_parse` that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn z3r_free(sram: *mut Z3rSram) {
+    if !sram.is_null() {
+        drop(Box::from_raw(sram));
+    }
+}
+
+/// Reads a stat as a number. `stat` is a `Z3rStat` and anything else
+/// returns `Z3R_ERROR_UNKNOWN_FIELD`. Times are in frames and fractions give
+/// their numerator.
+///
+/// # Safety
+///
+/// `sram` must be a live handle and `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn z3r_stat(sram: *const Z3rSram, stat: u32, out: *mut u32) -> Z3rError {
+    if sram.is_null() || out.is_null() {
+        return Z3rError::NullPointer;
+    }
+    match Z3rStat::from_u32(stat) {
+        Some(stat) => guard(|| stat_number(&*sram, stat.name(), &mut *out)),
+        None => Z3rError::UnknownField,
+    }
+}
+
+/// Like `z3r_stat` but takes a `read_stats` name such as `"small keys"`.
+///
+/// # Safety
+///
+/// `sram` must be a live handle, `name` a NUL-terminated string and `out`
+/// must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn z3r_stat_by_name(
+    sram: *const Z3rSram,
+    name: *const c_char,
+    out: *mut u32,
+) -> Z3rError {
+    if sram.is_null() || name.is_null() || out.is_null() {
+        return Z3rError::NullPointer;
+    }
+    match CStr::from_ptr(name).to_str() {
+        Ok(name) => guard(|| stat_number(&*sram, name, &mut *out)),
+        Err(_) => Z3rError::InvalidName,
+    }
+}
+
+/// Writes a stat's text, e.g. `00:12:34.56` for times, to `buf` with a
+/// terminating NUL. This is the only way to read meta values such as
+/// `"hash id"`, which read as `none` when the file doesn't have them.
+///
+/// # Safety
+///
+/// `sram` must be a live handle, `name` a NUL-terminated string and `buf`
+/// must have `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn z3r_stat_text(
+    sram: *const Z3rSram,
+    name: *const c_char,
+    buf: *mut c_char,
+    len: usize,
+) -> Z3rError {
+    if sram.is_null() || name.is_null() || buf.is_null() {
+        return Z3rError::NullPointer;
+    }
+    let name = match CStr::from_ptr(name).to_str() {
+        Ok(name) => name,
+        Err(_) => return Z3rError::InvalidName,
+    };
+    guard(|| {
+        let text = match (*sram).parsed.stats.get(name) {
+            Some(stat) => stat.to_string(),
+            None => return Z3rError::UnknownField,
+        };
+        if text.len() >= len {
+            return Z3rError::BufferTooSmall;
+        }
+        ptr::copy_nonoverlapping(text.as_ptr() as *const c_char, buf, text.len());
+        *buf.add(text.len()) = 0;
+
+        Z3rError::Ok
+    })
+}
+
+/// Reads an equipment value by its `read_equipment` name. Items you either
+/// have or don't read as 0 or 1.
+///
+/// # Safety
+///
+/// `sram` must be a live handle, `name` a NUL-terminated string and `out`
+/// must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn z3r_equipment(
+    sram: *const Z3rSram,
+    name: *const c_char,
+    out: *mut u32,
+) -> Z3rError {
+    if sram.is_null() || name.is_null() || out.is_null() {
+        return Z3rError::NullPointer;
+    }
+    let name = match CStr::from_ptr(name).to_str() {
+        Ok(name) => name,
+        Err(_) => return Z3rError::InvalidName,
+    };
+    guard(|| match (*sram).parsed.equipment.get(name) {
+        Some(equip) => {
+            *out = Z3REquip::value(equip);
+            Z3rError::Ok
+        }
+        None => Z3rError::UnknownField,
+    })
+}
+
+/// A static, NUL-terminated description of an error code. `error` is a
+/// `Z3rError` and anything else is described as an unknown error.
+#[no_mangle]
+pub extern "C" fn z3r_error_message(error: u32) -> *const c_char {
+    let error = match Z3rError::from_u32(error) {
+        Some(error) => error,
+        None => return "Unknown error\0".as_ptr() as *const c_char,
+    };
+    let message: &'static str = match error {
+        Z3rError::Ok => "No error\0",
+        Z3rError::NullPointer => "Null pointer argument\0",
+        Z3rError::InvalidSize => "Validation Error: Unexpected file size\0",
+        Z3rError::InvalidFile => "Validation Error: Invalid file\0",
+        Z3rError::InvalidRomName => "Validation Error: Invalid ROM name\0",
+        Z3rError::InvalidChecksum => "Validation Error: Invalid checksum\0",
+        Z3rError::ParseFailed => "Couldn't parse file\0",
+        Z3rError::UnknownField => "Unknown field\0",
+        Z3rError::NotANumber => "Field isn't a number\0",
+        Z3rError::BufferTooSmall => "Buffer too small\0",
+        Z3rError::InvalidName => "Field name isn't valid UTF-8\0",
+        Z3rError::Panic => "Internal error\0",
+    };
+
+    message.as_ptr() as *const c_char
+}
+
+// Keeps a panic from unwinding into the caller's frames, which is undefined
+// behavior across `extern "C"`.
+fn guard<F: FnOnce() -> Z3rError>(f: F) -> Z3rError {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(Z3rError::Panic)
+}
+
+fn stat_number(sram: &Z3rSram, name: &str, out: &mut u32) -> Z3rError {
+    match sram.parsed.stats.get(name).map(u32::try_from) {
+        Some(Ok(value)) => {
+            *out = value;
+            Z3rError::Ok
+        }
+        Some(Err(_)) => Z3rError::NotANumber,
+        None => Z3rError::UnknownField,
+    }
+}
@@ -0,0 +1,175 @@
+use anyhow::{anyhow, Result};
+use std::{env, fs, path::PathBuf, process};
+
+use z3r_sramr::{overlay::Overlay, parse_sram, watch::Watcher};
+
+const USAGE: &str = "\
+Usage:
+    z3r-sramr overlay <sram> <output dir> [options]
+    z3r-sramr watch <dir> [options]
+
+Overlay options:
+    --stat <name>         Write a stat, e.g. --stat \"total time\"
+    --equipment <name>    Write an equipment value, e.g. --equipment sword
+    --watch               Keep running and rewrite changed values whenever
+                          the SRAM is saved
+    --no-validate         Parse files that fail validation
+
+Each field is written to \"<output dir>/stat-<name>.txt\" or
+\"<output dir>/equip-<name>.txt\". Unknown names are an error.
+
+Watch options:
+    --extension <ext>     Watch files with this extension instead of .srm
+    --no-validate         Parse files that fail validation
+
+Prints what changed every time a file in <dir> is saved.";
+
+struct OverlayArgs {
+    sram: PathBuf,
+    overlay: Overlay,
+    watch: bool,
+    validate: bool,
+}
+
+fn parse_overlay_args(mut args: impl Iterator<Item = String>) -> Result<OverlayArgs> {
+    let sram = args.next().ok_or_else(|| anyhow!("Missing SRAM path"))?;
+    let dir = args
+        .next()
+        .ok_or_else(|| anyhow!("Missing output directory"))?;
+    let mut overlay = Overlay::new(dir);
+    let mut watch = false;
+    let mut validate = true;
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| anyhow!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--stat" => overlay = overlay.with_stat(&value()?)?,
+            "--equipment" => overlay = overlay.with_equipment(&value()?)?,
+            "--watch" => watch = true,
+            "--no-validate" => validate = false,
+            _ => return Err(anyhow!("Unknown option {}", arg)),
+        }
+    }
+    if overlay.fields().is_empty() {
+        return Err(anyhow!(
+            "No fields chosen, add at least one --stat or --equipment"
+        ));
+    }
+
+    Ok(OverlayArgs {
+        sram: PathBuf::from(sram),
+        overlay,
+        watch,
+        validate,
+    })
+}
+
+fn overlay(args: OverlayArgs) -> Result<()> {
+    let OverlayArgs {
+        sram,
+        mut overlay,
+        watch,
+        validate,
+    } = args;
+    fs::create_dir_all(overlay.dir())?;
+    if !watch {
+        let parsed = parse_sram(&fs::read(&sram)?, validate)?;
+        for path in overlay.update(&parsed)? {
+            println!("Wrote {}", path.display());
+        }
+        return Ok(());
+    }
+
+    // Watch the SRAM's directory for files like it and skip the others
+    let dir = match sram.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let extension = sram.extension().and_then(|e| e.to_str()).unwrap_or("srm");
+    let watcher = Watcher::new(dir)
+        .with_extension(extension)
+        .with_validation(validate);
+    Ok(watcher.run(|update| {
+        if update.path.file_name() != sram.file_name() {
+            return true;
+        }
+        match update.result.and_then(|parsed| overlay.update(&parsed)) {
+            Ok(paths) => paths.iter().for_each(|p| println!("Wrote {}", p.display())),
+            Err(e) => eprintln!("{}: {}", update.path.display(), e),
+        }
+        true
+    })?)
+}
+
+struct WatchArgs {
+    dir: PathBuf,
+    extension: String,
+    validate: bool,
+}
+
+fn parse_watch_args(mut args: impl Iterator<Item = String>) -> Result<WatchArgs> {
+    let dir = args.next().ok_or_else(|| anyhow!("Missing directory"))?;
+    let mut extension = String::from("srm");
+    let mut validate = true;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--extension" => {
+                extension = args
+                    .next()
+                    .ok_or_else(|| anyhow!("Missing value for {}", arg))?
+            }
+            "--no-validate" => validate = false,
+            _ => return Err(anyhow!("Unknown option {}", arg)),
+        }
+    }
+
+    Ok(WatchArgs {
+        dir: PathBuf::from(dir),
+        extension,
+        validate,
+    })
+}
+
+fn watch(args: WatchArgs) -> Result<()> {
+    let watcher = Watcher::new(args.dir)
+        .with_extension(&args.extension)
+        .with_validation(args.validate);
+    Ok(watcher.run(|update| {
+        let path = update.path.display();
+        match update.result {
+            Ok(_) if update.events.is_empty() => println!("{}: saved", path),
+            Ok(_) => {
+                for event in update.events {
+                    println!("{}: {} at frame {}", path, event.kind, event.frame);
+                }
+            }
+            Err(e) => eprintln!("{}: {}", path, e),
+        }
+        true
+    })?)
+}
+
+fn run() -> Result<()> {
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("overlay") => overlay(parse_overlay_args(args)?),
+        Some("watch") => watch(parse_watch_args(args)?),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => Err(anyhow!("Unknown command {}\n\n{}", command, USAGE)),
+        None => Err(anyhow!("{}", USAGE)),
+    }
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,292 @@
+use core::{cmp::Ordering, convert::TryFrom};
+
+use crate::{prelude::*, Map, ParsedSram};
+
+/// One runner's file from an async race.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunnerFile {
+    pub runner: String,
+    pub parsed: ParsedSram,
+}
+
+/// A stat compared when two runners have the same total time. Times compare
+/// by frames and fractions by their numerator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TieBreaker {
+    /// The runner with the lower value places higher, e.g. `"deaths"`.
+    Lowest(&'static str),
+    /// The runner with the higher value places higher, e.g. `"collection rate"`.
+    Highest(&'static str),
+}
+
+impl TieBreaker {
+    fn compare(&self, a: &ParsedSram, b: &ParsedSram) -> Ordering {
+        match self {
+            Self::Lowest(name) => compare_missing_last(stat_value(a, name), stat_value(b, name)),
+            Self::Highest(name) => match (stat_value(a, name), stat_value(b, name)) {
+                (Some(a), Some(b)) => b.cmp(&a),
+                (a, b) => compare_missing_last(a, b),
+            },
+        }
+    }
+}
+
+/// A runner's place on a seed. Runners that can't be separated by total time
+/// or any tie-breaker share a rank, and the next rank is skipped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Standing {
+    pub rank: usize,
+    pub runner: String,
+    pub total_time: Option<u32>,
+    pub finished: bool,
+}
+
+/// The spread of one stat across the runners that have it. With an even
+/// number of runners the median is the mean of the middle two, rounded down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StatRange {
+    pub min: u32,
+    pub median: u32,
+    pub max: u32,
+}
+
+/// Every file with the same ROM name, which identifies the seed for both VT
+/// and ER files. Files without a readable ROM name are grouped under `None`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeedResults {
+    pub rom_name: Option<String>,
+    pub standings: Vec<Standing>,
+    pub stats: Map<&'static str, StatRange>,
+}
+
+/// Ranks finished runs by total time, then by each tie-breaker in the order
+/// they were added. Unfinished or forfeited runs are ranked the same way after
+/// every finished run, and files without a total time are placed last.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Leaderboard {
+    tie_breakers: Vec<TieBreaker>,
+}
+
+impl Leaderboard {
+    pub fn new() -> Self {
+        Leaderboard {
+            tie_breakers: Vec::new(),
+        }
+    }
+
+    pub fn with_tie_breaker(mut self, tie_breaker: TieBreaker) -> Self {
+        self.tie_breakers.push(tie_breaker);
+        self
+    }
+
+    /// Groups `files` by ROM name and ranks each group. Groups are sorted by
+    /// ROM name.
+    pub fn aggregate(&self, files: &[RunnerFile]) -> Vec<SeedResults> {
+        let mut groups: Map<Option<String>, Vec<&RunnerFile>> = Map::new();
+        for file in files {
+            let rom_name = file.parsed.rom.as_ref().map(|r| r.name.clone());
+            groups.entry(rom_name).or_default().push(file);
+        }
+        let mut results: Vec<SeedResults> = groups
+            .into_iter()
+            .map(|(rom_name, group)| SeedResults {
+                standings: self.rank(&group),
+                stats: stat_ranges(&group),
+                rom_name,
+            })
+            .collect();
+        results.sort_by(|a, b| a.rom_name.cmp(&b.rom_name));
+
+        results
+    }
+
+    /// Ranks `files` as one seed, without grouping them.
+    pub fn rank(&self, files: &[&RunnerFile]) -> Vec<Standing> {
+        let mut sorted = files.to_vec();
+        sorted.sort_by(|a, b| self.compare(&a.parsed, &b.parsed));
+
+        let mut standings: Vec<Standing> = Vec::with_capacity(sorted.len());
+        for (i, file) in sorted.iter().enumerate() {
+            let rank = match i {
+                0 => 1,
+                _ if self.compare(&sorted[i - 1].parsed, &file.parsed) == Ordering::Equal => {
+                    standings[i - 1].rank
+                }
+                _ => i + 1,
+            };
+            standings.push(Standing {
+                rank,
+                runner: file.runner.clone(),
+                total_time: stat_value(&file.parsed, "total time"),
+                finished: file.parsed.completion.is_finished(),
+            });
+        }
+
+        standings
+    }
+
+    fn compare(&self, a: &ParsedSram, b: &ParsedSram) -> Ordering {
+        let finished_first = b.completion.is_finished().cmp(&a.completion.is_finished());
+        let by_time = finished_first.then_with(|| {
+            compare_missing_last(stat_value(a, "total time"), stat_value(b, "total time"))
+        });
+
+        self.tie_breakers
+            .iter()
+            .fold(by_time, |order, t| order.then_with(|| t.compare(a, b)))
+    }
+}
+
+fn stat_value(parsed: &ParsedSram, name: &str) -> Option<u32> {
+    parsed.stats.get(name).and_then(|s| u32::try_from(s).ok())
+}
+
+// Orders smaller values first and missing values after everything else
+fn compare_missing_last(a: Option<u32>, b: Option<u32>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+fn stat_ranges(files: &[&RunnerFile]) -> Map<&'static str, StatRange> {
+    let mut values: Map<&'static str, Vec<u32>> = Map::new();
+    for file in files {
+        for (name, stat) in file.parsed.stats.iter() {
+            if let Ok(value) = u32::try_from(stat) {
+                values.entry(name).or_default().push(value);
+            }
+        }
+    }
+
+    values
+        .into_iter()
+        .map(|(name, mut v)| {
+            v.sort_unstable();
+            let mid = v.len() / 2;
+            let median = match v.len() % 2 {
+                0 => ((v[mid - 1] as u64 + v[mid] as u64) / 2) as u32,
+                _ => v[mid],
+            };
+            let range = StatRange {
+                min: v[0],
+                median,
+                max: v[v.len() - 1],
+            };
+            (name, range)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_sram, test_util::blank};
+
+    fn runner(
+        name: &str,
+        rom: &[u8; 21],
+        total_time: u32,
+        deaths: u8,
+        finished: bool,
+    ) -> RunnerFile {
+        let mut sram = blank();
+        sram[0x2000..0x2015].copy_from_slice(rom);
+        sram[0x43E..0x442].copy_from_slice(&total_time.to_le_bytes());
+        sram[0x449] = deaths;
+        sram[0x443] = finished as u8;
+
+        RunnerFile {
+            runner: name.to_string(),
+            parsed: parse_sram(&sram, false).unwrap(),
+        }
+    }
+
+    fn places(standings: &[Standing]) -> Vec<(usize, &str)> {
+        standings
+            .iter()
+            .map(|s| (s.rank, s.runner.as_str()))
+            .collect()
+    }
+
+    const SEED: &[u8; 21] = b"VT 1234567890        ";
+
+    #[test]
+    fn groups_er_seeds_by_rom_name() {
+        let files = [
+            runner("a", b"ER400_1_123456789    ", 100, 0, true),
+            runner("b", b"ER400_1_987654321    ", 200, 0, true),
+            runner("c", b"ER400_1_123456789    ", 300, 0, true),
+        ];
+        let results = Leaderboard::new().aggregate(&files);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].rom_name.as_deref(), Some("ER400_1_123456789"));
+        let rom = files[0].parsed.rom.as_ref().unwrap();
+        assert_eq!((rom.team, rom.seed), (Some(1), Some(123456789)));
+        assert_eq!(places(&results[0].standings), [(1, "a"), (2, "c")]);
+        assert_eq!(places(&results[1].standings), [(1, "b")]);
+    }
+
+    #[test]
+    fn ranks_finished_runs_first() {
+        let files = [
+            runner("forfeit", SEED, 50, 0, false),
+            runner("slow", SEED, 300, 0, true),
+            runner("fast", SEED, 200, 0, true),
+        ];
+        let standings = &Leaderboard::new().aggregate(&files)[0].standings;
+
+        assert_eq!(
+            places(standings),
+            [(1, "fast"), (2, "slow"), (3, "forfeit")]
+        );
+        assert!(!standings[2].finished);
+    }
+
+    #[test]
+    fn tie_breakers_separate_equal_times() {
+        let files = [
+            runner("a", SEED, 100, 3, true),
+            runner("b", SEED, 100, 1, true),
+            runner("c", SEED, 90, 5, true),
+        ];
+        let board = Leaderboard::new().with_tie_breaker(TieBreaker::Lowest("deaths"));
+        assert_eq!(
+            places(&board.aggregate(&files)[0].standings),
+            [(1, "c"), (2, "b"), (3, "a")]
+        );
+
+        let board = Leaderboard::new().with_tie_breaker(TieBreaker::Highest("deaths"));
+        assert_eq!(
+            places(&board.aggregate(&files)[0].standings),
+            [(1, "c"), (2, "a"), (3, "b")]
+        );
+    }
+
+    #[test]
+    fn unseparated_runners_share_a_rank() {
+        let files = [
+            runner("a", SEED, 100, 0, true),
+            runner("b", SEED, 100, 0, true),
+            runner("c", SEED, 200, 0, true),
+        ];
+        let standings = &Leaderboard::new().aggregate(&files)[0].standings;
+
+        assert_eq!(places(standings), [(1, "a"), (1, "b"), (3, "c")]);
+    }
+
+    #[test]
+    fn stat_ranges_use_the_lower_median() {
+        let files = [
+            runner("a", SEED, 100, 1, true),
+            runner("b", SEED, 200, 2, true),
+        ];
+        let stats = &Leaderboard::new().aggregate(&files)[0].stats;
+        let deaths = stats["deaths"];
+
+        assert_eq!((deaths.min, deaths.median, deaths.max), (1, 1, 2));
+    }
+}
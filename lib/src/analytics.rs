@@ -0,0 +1,189 @@
+use core::{convert::TryFrom, fmt};
+
+use crate::{error::Result, prelude::*, validate_sram, view::SramView};
+
+// The randomizer's timers count frames at 60 per second
+const FRAMES_PER_MINUTE: f64 = 3600.0;
+const FRAMES_PER_HOUR: f64 = 216000.0;
+
+/// Stats that count items picked up, as opposed to dungeon prizes and
+/// dungeon items.
+const ITEM_STATS: [&str; 8] = [
+    "y items",
+    "a items",
+    "swords",
+    "shields",
+    "mails",
+    "capacity upgrades",
+    "heart containers",
+    "heart pieces",
+];
+
+/// The items the randomizer records a find time for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Milestone {
+    FirstSword,
+    Boots,
+    Flute,
+    Mirror,
+}
+
+impl Milestone {
+    pub const ALL: [Milestone; 4] = [Self::FirstSword, Self::Boots, Self::Flute, Self::Mirror];
+
+    /// The `read_stats` name of the milestone's time.
+    pub fn stat_name(&self) -> &'static str {
+        match self {
+            Self::FirstSword => "first sword",
+            Self::Boots => "boots found",
+            Self::Flute => "flute found",
+            Self::Mirror => "mirror found",
+        }
+    }
+
+    /// Frame the milestone was reached, or `None` if it hasn't been.
+    pub fn frame(&self, view: &SramView) -> Option<u32> {
+        let frame = match self {
+            Self::FirstSword => view.first_sword(),
+            Self::Boots => view.boots_found(),
+            Self::Flute => view.flute_found(),
+            Self::Mirror => view.mirror_found(),
+        };
+
+        Some(frame).filter(|f| *f > 0)
+    }
+}
+
+impl fmt::Display for Milestone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::FirstSword => write!(f, "First Sword"),
+            Self::Boots => write!(f, "Boots"),
+            Self::Flute => write!(f, "Flute"),
+            Self::Mirror => write!(f, "Mirror"),
+        }
+    }
+}
+
+/// Frames between a milestone and the one reached before it. `from` is
+/// `None` for the first milestone, which is timed from the start of the run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MilestoneGap {
+    pub from: Option<Milestone>,
+    pub to: Milestone,
+    pub frames: u32,
+}
+
+/// Metrics derived from the raw stats. Rates are per unit of total in-game
+/// time and are zero when no time has passed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RaceMetrics {
+    /// Lag frames as a percentage of total time.
+    pub lag_percentage: f64,
+    /// Total time minus time spent in menus, in frames.
+    pub time_excluding_menus: u32,
+    /// Items picked up per minute. Dungeon items and prizes aren't counted.
+    pub items_per_minute: f64,
+    /// Locations checked per hour, from the collection rate.
+    pub locations_per_hour: f64,
+    pub deaths_per_hour: f64,
+    /// Milestones reached, in the order they were reached.
+    pub milestone_gaps: Vec<MilestoneGap>,
+}
+
+pub fn read_race_metrics(sram: &[u8], validate: bool) -> Result<RaceMetrics> {
+    if validate {
+        validate_sram(sram)?;
+    }
+    let view = SramView::new(sram)?;
+    let total_time = view.total_time();
+    let per = |count: u32, frames: f64| -> f64 {
+        match total_time {
+            0 => 0.0,
+            t => count as f64 * frames / t as f64,
+        }
+    };
+
+    let mut items = 0u32;
+    for name in ITEM_STATS.iter() {
+        items += u32::try_from(&view.stat(name)?)?;
+    }
+
+    let mut reached: Vec<(Milestone, u32)> = Milestone::ALL
+        .iter()
+        .filter_map(|m| m.frame(&view).map(|f| (*m, f)))
+        .collect();
+    reached.sort_by_key(|(_, frame)| *frame);
+    let mut milestone_gaps: Vec<MilestoneGap> = Vec::with_capacity(reached.len());
+    let mut previous: Option<(Milestone, u32)> = None;
+    for (milestone, frame) in reached {
+        milestone_gaps.push(MilestoneGap {
+            from: previous.map(|(m, _)| m),
+            to: milestone,
+            frames: frame - previous.map_or(0, |(_, f)| f),
+        });
+        previous = Some((milestone, frame));
+    }
+
+    Ok(RaceMetrics {
+        lag_percentage: per(view.lag_time(), 100.0),
+        time_excluding_menus: total_time.saturating_sub(view.menu_time()),
+        items_per_minute: per(items, FRAMES_PER_MINUTE),
+        locations_per_hour: per(view.collection_rate(), FRAMES_PER_HOUR),
+        deaths_per_hour: per(view.deaths(), FRAMES_PER_HOUR),
+        milestone_gaps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::edited;
+
+    #[test]
+    fn milestone_gaps_follow_the_order_they_were_reached() {
+        // Boots at 20, first sword at 50 and mirror at 90, flute not found
+        let sram = edited(&[(0x45C, 20), (0x458, 50), (0x464, 90)]);
+        let metrics = read_race_metrics(&sram, true).unwrap();
+        assert_eq!(
+            metrics.milestone_gaps,
+            [
+                MilestoneGap {
+                    from: None,
+                    to: Milestone::Boots,
+                    frames: 20,
+                },
+                MilestoneGap {
+                    from: Some(Milestone::Boots),
+                    to: Milestone::FirstSword,
+                    frames: 30,
+                },
+                MilestoneGap {
+                    from: Some(Milestone::FirstSword),
+                    to: Milestone::Mirror,
+                    frames: 40,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rates_are_zero_without_time() {
+        let metrics = read_race_metrics(&edited(&[(0x449, 3)]), true).unwrap();
+        assert_eq!(metrics.lag_percentage, 0.0);
+        assert_eq!(metrics.items_per_minute, 0.0);
+        assert_eq!(metrics.locations_per_hour, 0.0);
+        assert_eq!(metrics.deaths_per_hour, 0.0);
+        assert!(metrics.milestone_gaps.is_empty());
+    }
+
+    #[test]
+    fn rates_are_per_unit_of_total_time() {
+        // One hour is 216000 frames, none of them in the game loop
+        let sram = edited(&[(0x43E, 0xC0), (0x43F, 0x4B), (0x440, 0x03), (0x449, 3)]);
+        let metrics = read_race_metrics(&sram, true).unwrap();
+        assert_eq!(metrics.deaths_per_hour, 3.0);
+        assert_eq!(metrics.lag_percentage, 100.0);
+        assert_eq!(metrics.time_excluding_menus, 216000);
+    }
+}
@@ -0,0 +1,234 @@
+use std::{
+    borrow::Cow,
+    convert::TryFrom,
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{error::Result, parse_sram, Map, ParsedSram};
+
+/// A file to parse, either read from disk when it's parsed or already in
+/// memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BatchInput<'a> {
+    Path(PathBuf),
+    Buffer { name: String, sram: &'a [u8] },
+}
+
+impl<'a> BatchInput<'a> {
+    pub fn name(&self) -> String {
+        match self {
+            Self::Path(path) => path.display().to_string(),
+            Self::Buffer { name, .. } => name.clone(),
+        }
+    }
+
+    pub(crate) fn load(&self) -> Result<Cow<'a, [u8]>> {
+        match self {
+            Self::Path(path) => Ok(Cow::Owned(fs::read(path)?)),
+            Self::Buffer { sram, .. } => Ok(Cow::Borrowed(*sram)),
+        }
+    }
+}
+
+impl From<PathBuf> for BatchInput<'_> {
+    fn from(path: PathBuf) -> Self {
+        Self::Path(path)
+    }
+}
+
+impl From<&Path> for BatchInput<'_> {
+    fn from(path: &Path) -> Self {
+        Self::Path(path.to_path_buf())
+    }
+}
+
+/// The outcome for one input. Errors are kept so one bad file doesn't stop
+/// the rest of the batch.
+#[derive(Debug)]
+pub struct BatchResult {
+    pub source: String,
+    pub result: Result<ParsedSram>,
+}
+
+/// Parses every input, in parallel when the `rayon` feature is enabled.
+/// Results are in the same order as `inputs`.
+pub fn parse_batch(inputs: &[BatchInput], validate: bool) -> Vec<BatchResult> {
+    #[cfg(feature = "rayon")]
+    let iter = inputs.par_iter();
+    #[cfg(not(feature = "rayon"))]
+    let iter = inputs.iter();
+
+    iter.map(|input| parse_input(input, validate)).collect()
+}
+
+fn parse_input(input: &BatchInput, validate: bool) -> BatchResult {
+    BatchResult {
+        source: input.name(),
+        result: input.load().and_then(|sram| parse_sram(&sram, validate)),
+    }
+}
+
+/// Running totals for one numeric stat. Times are in frames and fractions
+/// count their numerator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StatSummary {
+    pub count: u64,
+    pub min: u32,
+    pub max: u32,
+    pub total: u64,
+}
+
+impl StatSummary {
+    fn new(value: u32) -> Self {
+        StatSummary {
+            count: 1,
+            min: value,
+            max: value,
+            total: value as u64,
+        }
+    }
+
+    fn merge(&mut self, other: &StatSummary) {
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.total += other.total;
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.total as f64 / self.count as f64
+    }
+}
+
+/// Totals for a batch that was summarized without keeping the parsed files.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BatchSummary {
+    pub files: u64,
+    pub parsed: u64,
+    pub errors: Vec<(String, String)>,
+    pub stats: Map<&'static str, StatSummary>,
+}
+
+impl BatchSummary {
+    /// Folds one result into the summary and drops the parsed file.
+    pub fn with_result(mut self, result: BatchResult) -> Self {
+        self.files += 1;
+        let parsed = match result.result {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                self.errors.push((result.source, e.to_string()));
+                return self;
+            }
+        };
+        self.parsed += 1;
+        for (name, stat) in parsed.stats.iter() {
+            if let Ok(value) = u32::try_from(stat) {
+                self.stats
+                    .entry(name)
+                    .and_modify(|s| s.merge(&StatSummary::new(value)))
+                    .or_insert_with(|| StatSummary::new(value));
+            }
+        }
+
+        self
+    }
+
+    /// Combines summaries of two separate batches.
+    pub fn merge(mut self, other: BatchSummary) -> Self {
+        self.files += other.files;
+        self.parsed += other.parsed;
+        self.errors.extend(other.errors);
+        for (name, summary) in other.stats {
+            self.stats
+                .entry(name)
+                .and_modify(|s| s.merge(&summary))
+                .or_insert(summary);
+        }
+
+        self
+    }
+}
+
+/// Parses each input and folds it into a `BatchSummary` straight away, so
+/// only the files currently being parsed are held in memory.
+pub fn summarize_batch<'a, I>(inputs: I, validate: bool) -> BatchSummary
+where
+    I: IntoIterator<Item = BatchInput<'a>>,
+    I::IntoIter: Send,
+{
+    #[cfg(feature = "rayon")]
+    {
+        inputs
+            .into_iter()
+            .par_bridge()
+            .map(|input| parse_input(&input, validate))
+            .fold(BatchSummary::default, BatchSummary::with_result)
+            .reduce(BatchSummary::default, BatchSummary::merge)
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        inputs
+            .into_iter()
+            .map(|input| parse_input(&input, validate))
+            .fold(BatchSummary::default(), BatchSummary::with_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::edited;
+
+    fn buffer<'a>(name: &str, sram: &'a [u8]) -> BatchInput<'a> {
+        BatchInput::Buffer {
+            name: name.to_string(),
+            sram,
+        }
+    }
+
+    #[test]
+    fn results_keep_input_order_and_errors() {
+        let good = edited(&[]);
+        let results = parse_batch(&[buffer("short", &good[..16]), buffer("good", &good)], true);
+        assert_eq!(results[0].source, "short");
+        assert!(results[0].result.is_err());
+        assert_eq!(results[1].source, "good");
+        assert!(results[1].result.is_ok());
+    }
+
+    #[test]
+    fn merged_summaries_keep_errors_and_combine_stats() {
+        let two = edited(&[(0x449, 2)]);
+        let five = edited(&[(0x449, 5)]);
+        let mut bad = edited(&[]);
+        bad[0x4FE] ^= 0xFF;
+
+        let first = summarize_batch(vec![buffer("two", &two), buffer("bad", &bad)], true);
+        let second = summarize_batch(vec![buffer("five", &five)], true);
+        let summary = first.merge(second);
+
+        assert_eq!(summary.files, 3);
+        assert_eq!(summary.parsed, 2);
+        assert_eq!(
+            summary.errors,
+            [(
+                "bad".to_string(),
+                "Validation Error: Invalid checksum".to_string()
+            )]
+        );
+        assert_eq!(
+            summary.stats["deaths"],
+            StatSummary {
+                count: 2,
+                min: 2,
+                max: 5,
+                total: 7,
+            }
+        );
+        assert_eq!(summary.stats["deaths"].mean(), 3.5);
+    }
+}
@@ -0,0 +1,287 @@
+use core::fmt;
+
+use crate::{equipment::GOAL_ITEMS, error::Result, room_flags, validate_sram, view::SramView};
+
+/// How far a run got. Finished runs carry the in-game time, in frames, that
+/// the randomizer froze when the game ended.
+///
+/// The save has no record of which goal the seed had, so `PedestalPulled` is
+/// a best guess: a Fast Ganon run that pulled the pedestal on the way and
+/// finished before Ganon's room flag was saved never needed Agahnim 2, and is
+/// reported as `PedestalPulled` too. The progress indicator at 0x3C5 stops
+/// changing once Agahnim 1 is defeated, so it can't tell the two apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum Completion {
+    InProgress,
+    GanonDefeated { final_time: u32 },
+    TriforceHuntComplete { final_time: u32 },
+    PedestalPulled { final_time: u32 },
+}
+
+impl Completion {
+    pub fn is_finished(&self) -> bool {
+        *self != Self::InProgress
+    }
+
+    pub fn final_time(&self) -> Option<u32> {
+        match self {
+            Self::InProgress => None,
+            Self::GanonDefeated { final_time }
+            | Self::TriforceHuntComplete { final_time }
+            | Self::PedestalPulled { final_time } => Some(*final_time),
+        }
+    }
+}
+
+impl fmt::Display for Completion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InProgress => write!(f, "In Progress"),
+            Self::GanonDefeated { .. } => write!(f, "Ganon Defeated"),
+            Self::TriforceHuntComplete { .. } => write!(f, "Triforce Hunt Complete"),
+            Self::PedestalPulled { .. } => write!(f, "Pedestal Pulled"),
+        }
+    }
+}
+
+const GANON_ROOM: usize = 0x00;
+const AGAHNIM_2_ROOM: usize = 0x0D;
+
+/// Whether the randomizer has locked its stats. This happens when Link
+/// reaches the Triforce and the end of game sequence starts, whatever the
+/// goal was.
+pub(crate) fn game_finished(sram: &[u8]) -> bool {
+    sram[0x443] != 0
+}
+
+pub(crate) fn pedestal_pulled(sram: &[u8]) -> bool {
+    // Overworld area 0x80 is the pedestal, bit 6 of its flags is the item
+    sram[0x300] & 0x40 != 0
+}
+
+/// Classifies a file by how the game ended.
+///
+/// Ganon's room flag means Ganon was defeated, including in hunts where the
+/// pieces open the way to Ganon. Otherwise a finished file with triforce
+/// pieces is a completed hunt since goal items are only collected in hunts.
+/// Ganon's flag isn't always saved before the credits, and the pedestal can
+/// be pulled mid-run in any goal, so a pulled pedestal only counts as the
+/// ending when Agahnim 2, who opens the way to Ganon, was never defeated. Any
+/// other finished file counts as Ganon defeated. See [`Completion`] for the
+/// case this gets wrong.
+pub fn read_completion(sram: &[u8], validate: bool) -> Result<Completion> {
+    if validate {
+        validate_sram(sram)?;
+    }
+    let view = SramView::new(sram)?;
+    if !game_finished(sram) {
+        return Ok(Completion::InProgress);
+    }
+    let final_time = view.total_time();
+    let boss_defeated = |room: usize| room_flags(sram, room) & 0x0800 != 0;
+
+    if boss_defeated(GANON_ROOM) {
+        Ok(Completion::GanonDefeated { final_time })
+    } else if view.field(&GOAL_ITEMS)? > 0 {
+        Ok(Completion::TriforceHuntComplete { final_time })
+    } else if pedestal_pulled(sram) && !boss_defeated(AGAHNIM_2_ROOM) {
+        Ok(Completion::PedestalPulled { final_time })
+    } else {
+        Ok(Completion::GanonDefeated { final_time })
+    }
+}
+
+/// Piece counts for a hunt. These are seed settings rather than save data, so
+/// they have to come from the seed's metadata.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HuntGoal {
+    pub required: u32,
+    pub total: u32,
+}
+
+/// Triforce hunt progress from the goal item counter at 0x418.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TriforceHunt {
+    pub collected: u32,
+    pub required: Option<u32>,
+    pub total: Option<u32>,
+    pub turned_in: bool,
+}
+
+impl TriforceHunt {
+    pub fn remaining(&self) -> Option<u32> {
+        self.required.map(|r| r.saturating_sub(self.collected))
+    }
+
+    /// Whether enough pieces are held to turn in, when the requirement is
+    /// known. A hunt that was turned in is always complete.
+    pub fn can_turn_in(&self) -> Option<bool> {
+        match self.turned_in {
+            true => Some(true),
+            false => self.required.map(|r| self.collected >= r),
+        }
+    }
+}
+
+impl fmt::Display for TriforceHunt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.required, self.total) {
+            (Some(r), Some(t)) => write!(f, "{}/{} ({} total)", self.collected, r, t),
+            (Some(r), None) => write!(f, "{}/{}", self.collected, r),
+            _ => write!(f, "{}", self.collected),
+        }
+    }
+}
+
+/// Reads hunt progress. Turning the pieces in ends the game, so a finished
+/// file with pieces collected was turned in.
+pub fn read_triforce_hunt(
+    sram: &[u8],
+    validate: bool,
+    goal: Option<HuntGoal>,
+) -> Result<TriforceHunt> {
+    if validate {
+        validate_sram(sram)?;
+    }
+    let collected = SramView::new(sram)?.field(&GOAL_ITEMS)?;
+
+    Ok(TriforceHunt {
+        collected,
+        required: goal.map(|g| g.required),
+        total: goal.map(|g| g.total),
+        turned_in: collected > 0 && game_finished(sram),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::blank;
+
+    // A file whose stats were locked at 100 frames
+    fn finished() -> Vec<u8> {
+        let mut sram = blank();
+        sram[0x443] = 1;
+        sram[0x43E..0x442].copy_from_slice(&100u32.to_le_bytes());
+        sram
+    }
+
+    fn defeat_boss(sram: &mut [u8], room: usize) {
+        sram[room * 2 + 1] |= 0x08;
+    }
+
+    #[test]
+    fn unlocked_stats_are_in_progress() {
+        let mut sram = blank();
+        sram[0x300] = 0x40;
+        defeat_boss(&mut sram, GANON_ROOM);
+        assert_eq!(
+            read_completion(&sram, false).unwrap(),
+            Completion::InProgress
+        );
+    }
+
+    #[test]
+    fn goal_items_are_a_completed_hunt() {
+        let mut sram = finished();
+        sram[0x418] = 20;
+        assert_eq!(
+            read_completion(&sram, false).unwrap(),
+            Completion::TriforceHuntComplete { final_time: 100 }
+        );
+    }
+
+    #[test]
+    fn ganon_flag_beats_goal_items() {
+        // Hunts where the pieces open Ganon end when he's defeated
+        let mut sram = finished();
+        sram[0x418] = 20;
+        defeat_boss(&mut sram, GANON_ROOM);
+        assert_eq!(
+            read_completion(&sram, false).unwrap(),
+            Completion::GanonDefeated { final_time: 100 }
+        );
+    }
+
+    #[test]
+    fn ganon_flag_is_ganon_defeated() {
+        let mut sram = finished();
+        sram[0x300] = 0x40;
+        defeat_boss(&mut sram, GANON_ROOM);
+        assert_eq!(
+            read_completion(&sram, false).unwrap(),
+            Completion::GanonDefeated { final_time: 100 }
+        );
+    }
+
+    #[test]
+    fn pedestal_without_agahnim_2_is_pedestal_pulled() {
+        let mut sram = finished();
+        sram[0x300] = 0x40;
+        assert_eq!(
+            read_completion(&sram, false).unwrap(),
+            Completion::PedestalPulled { final_time: 100 }
+        );
+    }
+
+    #[test]
+    fn mid_run_pedestal_without_ganon_flag_is_ganon_defeated() {
+        let mut sram = finished();
+        sram[0x300] = 0x40;
+        defeat_boss(&mut sram, AGAHNIM_2_ROOM);
+        assert_eq!(
+            read_completion(&sram, false).unwrap(),
+            Completion::GanonDefeated { final_time: 100 }
+        );
+    }
+
+    #[test]
+    fn finished_without_flags_is_ganon_defeated() {
+        let completion = read_completion(&finished(), false).unwrap();
+        assert_eq!(completion, Completion::GanonDefeated { final_time: 100 });
+        assert_eq!(completion.final_time(), Some(100));
+    }
+
+    const GOAL: HuntGoal = HuntGoal {
+        required: 20,
+        total: 30,
+    };
+
+    #[test]
+    fn hunt_in_progress_counts_remaining_pieces() {
+        let mut sram = blank();
+        sram[0x418] = 12;
+        let hunt = read_triforce_hunt(&sram, false, Some(GOAL)).unwrap();
+        assert!(!hunt.turned_in);
+        assert_eq!(hunt.remaining(), Some(8));
+        assert_eq!(hunt.can_turn_in(), Some(false));
+        assert_eq!(hunt.to_string(), "12/20 (30 total)");
+
+        sram[0x418] = 22;
+        let hunt = read_triforce_hunt(&sram, false, Some(GOAL)).unwrap();
+        assert_eq!(hunt.remaining(), Some(0));
+        assert_eq!(hunt.can_turn_in(), Some(true));
+    }
+
+    #[test]
+    fn finished_hunt_was_turned_in() {
+        let mut sram = finished();
+        sram[0x418] = 20;
+        let hunt = read_triforce_hunt(&sram, false, None).unwrap();
+        assert!(hunt.turned_in);
+        assert_eq!(hunt.remaining(), None);
+        // The requirement isn't known but turning in means it was met
+        assert_eq!(hunt.can_turn_in(), Some(true));
+    }
+
+    #[test]
+    fn unknown_requirement_without_turn_in_is_unknown() {
+        let mut sram = blank();
+        sram[0x418] = 5;
+        let hunt = read_triforce_hunt(&sram, false, None).unwrap();
+        assert_eq!(hunt.can_turn_in(), None);
+        assert_eq!(hunt.to_string(), "5");
+    }
+}
@@ -0,0 +1,208 @@
+use core::{convert::TryFrom, fmt};
+
+use crate::{
+    error::Result, prelude::*, room_flags, stats::BOSS_STATS, validate_sram, view::SramView,
+};
+
+/// Which cross-check produced a finding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Check {
+    Swords,
+    Pendants,
+    Crystals,
+    Bosses,
+    CollectionRate,
+    ChestLocations,
+    LagTime,
+    MenuTime,
+}
+
+/// Something that can't happen in an unedited file. These are heuristics: a
+/// finding is a reason to look closer, not proof of tampering.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Finding {
+    pub check: Check,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}: {}", self.check, self.message)
+    }
+}
+
+/// Room of every boss counted by the bosses-by-sword stats: the ten dungeon
+/// bosses, both Agahnim fights and Ganon.
+const BOSS_ROOMS: [usize; 13] = [
+    0xC8, 0x33, 0x07, 0x20, 0x5A, 0x06, 0x29, 0xAC, 0xDE, 0x90, 0xA4, 0x0D, 0x00,
+];
+
+/// Cross-checks related fields and returns everything that doesn't add up.
+/// An empty list means no check found anything suspicious.
+pub fn check_consistency(sram: &[u8], validate: bool) -> Result<Vec<Finding>> {
+    if validate {
+        validate_sram(sram)?;
+    }
+    let view = SramView::new(sram)?;
+    let stat = |name: &str| -> Result<u32> { u32::try_from(&view.stat(name)?) };
+    let mut findings: Vec<Finding> = Vec::new();
+    let mut flag = |check: Check, message: String| findings.push(Finding { check, message });
+
+    let swords = stat("swords")?;
+    let sword = view.equipment("sword")?.value();
+    match sword {
+        0 if swords > 0 => flag(
+            Check::Swords,
+            format!("{} swords found but no sword equipped", swords),
+        ),
+        1..=4 if swords == 0 => flag(
+            Check::Swords,
+            format!("sword level {} equipped but no swords found", sword),
+        ),
+        255 if swords > 0 => flag(
+            Check::Swords,
+            format!("{} swords found on a swordless file", swords),
+        ),
+        _ => (),
+    }
+
+    let pendants = stat("pendants")?;
+    let pendant_bits = (sram[0x374] & 0x07).count_ones();
+    if pendants != pendant_bits {
+        flag(
+            Check::Pendants,
+            format!(
+                "{} pendants counted but {} pendant bits set",
+                pendants, pendant_bits
+            ),
+        );
+    }
+    let crystals = stat("crystals")?;
+    let crystal_bits = (sram[0x37A] & 0x7F).count_ones();
+    if crystals != crystal_bits {
+        flag(
+            Check::Crystals,
+            format!(
+                "{} crystals counted but {} crystal bits set",
+                crystals, crystal_bits
+            ),
+        );
+    }
+
+    let mut sword_bosses = 0u32;
+    for name in BOSS_STATS.iter() {
+        sword_bosses += stat(name)?;
+    }
+    let boss_flags = BOSS_ROOMS
+        .iter()
+        .filter(|room| room_flags(sram, **room) & 0x0800 != 0)
+        .count() as u32;
+    if sword_bosses != boss_flags {
+        flag(
+            Check::Bosses,
+            format!(
+                "{} bosses counted by sword but {} boss rooms cleared",
+                sword_bosses, boss_flags
+            ),
+        );
+    }
+
+    let collection_rate = view.collection_rate();
+    let opened_chests: u32 = (0..0x128)
+        .map(|room| ((room_flags(sram, room) >> 4) & 0x3F).count_ones())
+        .sum();
+    if opened_chests > collection_rate {
+        flag(
+            Check::CollectionRate,
+            format!(
+                "{} chests opened but collection rate is {}",
+                opened_chests, collection_rate
+            ),
+        );
+    }
+    if view.chest_locations() > collection_rate {
+        flag(
+            Check::ChestLocations,
+            format!(
+                "{} chest locations is more than the collection rate of {}",
+                view.chest_locations(),
+                collection_rate
+            ),
+        );
+    }
+
+    if view.loop_time() > view.total_time() {
+        flag(
+            Check::LagTime,
+            format!(
+                "loop time of {} frames is more than total time of {} frames",
+                view.loop_time(),
+                view.total_time()
+            ),
+        );
+    }
+    if view.menu_time() > view.total_time() {
+        flag(
+            Check::MenuTime,
+            format!(
+                "menu time of {} frames is more than total time of {} frames",
+                view.menu_time(),
+                view.total_time()
+            ),
+        );
+    }
+
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::edited;
+
+    fn checks(edits: &[(usize, u8)]) -> Vec<Check> {
+        check_consistency(&edited(edits), false)
+            .unwrap()
+            .into_iter()
+            .map(|f| f.check)
+            .collect()
+    }
+
+    #[test]
+    fn blank_and_consistent_files_pass() {
+        assert!(checks(&[]).is_empty());
+        // One sword found and the fighter's sword equipped
+        assert!(checks(&[(0x422, 0x20), (0x359, 1)]).is_empty());
+    }
+
+    #[test]
+    fn flags_swords() {
+        assert_eq!(checks(&[(0x359, 1)]), [Check::Swords]);
+        assert_eq!(checks(&[(0x422, 0x20)]), [Check::Swords]);
+        assert_eq!(checks(&[(0x422, 0x20), (0x359, 255)]), [Check::Swords]);
+    }
+
+    #[test]
+    fn flags_pendants_and_crystals() {
+        assert_eq!(checks(&[(0x374, 0x01)]), [Check::Pendants]);
+        assert_eq!(checks(&[(0x37A, 0x01)]), [Check::Crystals]);
+    }
+
+    #[test]
+    fn flags_bosses() {
+        // Armos Knights defeated without a bosses-by-sword count
+        assert_eq!(checks(&[(0xC8 * 2 + 1, 0x08)]), [Check::Bosses]);
+    }
+
+    #[test]
+    fn flags_collection_rate_and_chest_locations() {
+        assert_eq!(checks(&[(0x000, 0x10)]), [Check::CollectionRate]);
+        assert_eq!(checks(&[(0x442, 1)]), [Check::ChestLocations]);
+    }
+
+    #[test]
+    fn flags_lag_and_menu_time() {
+        assert_eq!(checks(&[(0x42E, 1)]), [Check::LagTime]);
+        assert_eq!(checks(&[(0x444, 1)]), [Check::MenuTime]);
+    }
+}
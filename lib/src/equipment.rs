@@ -1,9 +1,16 @@
-use anyhow::{anyhow, Result};
-use byteorder::{LittleEndian, ReadBytesExt};
-use std::{collections::HashMap, fmt, io::Cursor};
+use byteorder::{ByteOrder, LittleEndian};
+use core::fmt;
 
-use crate::{bitmask, validate_sram};
+use crate::{
+    error::{Error, Result},
+    field::{find_field, read_field, FieldKind, FieldSpec},
+    prelude::*,
+    validate_sram, Map,
+};
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
 pub enum Z3REquip {
     Has(bool),
     Number(u32),
@@ -13,25 +20,13 @@ impl Z3REquip {
     pub fn has(&self) -> bool {
         match self {
             Self::Has(b) => *b,
-            Self::Number(n) => {
-                if *n > 0 {
-                    true
-                } else {
-                    false
-                }
-            }
+            Self::Number(n) => *n > 0,
         }
     }
 
     pub fn value(&self) -> u32 {
         match self {
-            Self::Has(b) => {
-                if *b == true {
-                    1u32
-                } else {
-                    0u32
-                }
-            }
+            Self::Has(b) => *b as u32,
             Self::Number(n) => *n,
         }
     }
@@ -47,119 +42,414 @@ impl fmt::Display for Z3REquip {
     }
 }
 
-#[cfg_attr(rustfmt, rustfmt_skip)]
-pub fn read_equipment(sram: &[u8], validate: bool) -> Result<HashMap<&str, Z3REquip>> {
-    if validate == true {
-        validate_sram(&sram)?;
-    }
-    let mut cur = Cursor::new(sram);
-    let mut sram_equip: HashMap<&str, Z3REquip> = HashMap::with_capacity(25);
-    sram_equip.insert("current rupees", get_equipment(&mut cur, 0x362, 16, 0, false)?);
-    sram_equip.insert("current arrows", get_equipment(&mut cur, 0x377, 8, 0, false)?);
-    sram_equip.insert("current bombs", get_equipment(&mut cur, 0x343, 8, 0, false)?);
-    sram_equip.insert("current health", get_equipment(&mut cur, 0x36D, 8, 0, false)?);
-    sram_equip.insert("current magic", get_equipment(&mut cur, 0x36E, 8, 0, false)?);
-    sram_equip.insert("heart pieces", get_equipment(&mut cur, 0x36B, 8, 0, false)?);
-    sram_equip.insert("magic consumption", get_equipment(&mut cur, 0x37B, 8, 0, false)?);
-    sram_equip.insert("goal items", get_equipment(&mut cur, 0x418, 8, 0, false)?);
-    sram_equip.insert("bomb upgrades", get_equipment(&mut cur, 0x370, 8, 0, false)?);
-    sram_equip.insert("arrow upgrades", get_equipment(&mut cur, 0x371, 8, 0, false)?);
-    sram_equip.insert("fire rod", get_equipment(&mut cur, 0x345, 8, 0, true)?);
-    sram_equip.insert("ice rod", get_equipment(&mut cur, 0x346, 8, 0, true)?);
-    sram_equip.insert("bombos", get_equipment(&mut cur, 0x347, 8, 0, true)?);
-    sram_equip.insert("ether", get_equipment(&mut cur, 0x348, 8, 0, true)?);
-    sram_equip.insert("quake", get_equipment(&mut cur, 0x349, 8, 0, true)?);
-    sram_equip.insert("lamp", get_equipment(&mut cur, 0x34A, 8, 0, true)?);
-    sram_equip.insert("hammer", get_equipment(&mut cur, 0x34B, 8, 0, true)?);
-    sram_equip.insert("hookshot", get_equipment(&mut cur, 0x342, 8, 0, true)?);
-    sram_equip.insert("bug net", get_equipment(&mut cur, 0x34D, 8, 0, true)?);
-    sram_equip.insert("book", get_equipment(&mut cur, 0x34E, 8, 0, true)?);
-    sram_equip.insert("somaria", get_equipment(&mut cur, 0x350, 8, 0, true)?);
-    sram_equip.insert("byrna", get_equipment(&mut cur, 0x351, 8, 0, true)?);
-    sram_equip.insert("cape", get_equipment(&mut cur, 0x352, 8, 0, true)?);
-    sram_equip.insert("mirror", get_equipment(&mut cur, 0x353, 8, 0, false)?);
-    sram_equip.insert("gloves", get_equipment(&mut cur, 0x353, 8, 0, false)?);
-    sram_equip.insert("boots", get_equipment(&mut cur, 0x355, 8, 0, true)?);
-    sram_equip.insert("flippers", get_equipment(&mut cur, 0x356, 8, 0, true)?);
-    sram_equip.insert("moon pearl", get_equipment(&mut cur, 0x357, 8, 0, true)?);
-    sram_equip.insert("sword", get_equipment(&mut cur, 0x359, 8, 0, false)?);
-    sram_equip.insert("shield", get_equipment(&mut cur, 0x35A, 8, 0, false)?);
-    sram_equip.insert("mail", get_equipment(&mut cur, 0x35B, 8, 0, false)?);
-
-    let bottle_1 = get_equipment(&mut cur, 0x35C, 8, 0, false)?;
-    let bottle_2 = get_equipment(&mut cur, 0x35D, 8, 0, false)?;
-    let bottle_3 = get_equipment(&mut cur, 0x35E, 8, 0, false)?;
-    let bottle_4 = get_equipment(&mut cur, 0x35F, 8, 0, false)?;
-    let bottle_count: u32 = vec![bottle_1.value(),
-                                 bottle_2.value(),
-                                 bottle_3.value(),
-                                 bottle_4.value()]
+/// Every equipment value `read_equipment` decodes straight from SRAM. The
+/// swap inventory, capacities and bottle count are derived and aren't listed.
+#[rustfmt::skip]
+pub const EQUIPMENT_FIELDS: &[FieldSpec] = &[
+    FieldSpec::number("current rupees", 0x362, 16, 0),
+    FieldSpec::number("current arrows", 0x377, 8, 0),
+    FieldSpec::number("current bombs", 0x343, 8, 0),
+    FieldSpec::number("current health", 0x36D, 8, 0),
+    FieldSpec::number("current magic", 0x36E, 8, 0),
+    FieldSpec::number("heart pieces", 0x36B, 8, 0),
+    FieldSpec::number("magic consumption", 0x37B, 8, 0),
+    GOAL_ITEMS,
+    FieldSpec::number("bomb upgrades", 0x370, 8, 0),
+    FieldSpec::number("arrow upgrades", 0x371, 8, 0),
+    FieldSpec::flag("fire rod", 0x345, 8, 0),
+    FieldSpec::flag("ice rod", 0x346, 8, 0),
+    FieldSpec::flag("bombos", 0x347, 8, 0),
+    FieldSpec::flag("ether", 0x348, 8, 0),
+    FieldSpec::flag("quake", 0x349, 8, 0),
+    FieldSpec::flag("lamp", 0x34A, 8, 0),
+    FieldSpec::flag("hammer", 0x34B, 8, 0),
+    FieldSpec::flag("hookshot", 0x342, 8, 0),
+    FieldSpec::flag("bug net", 0x34D, 8, 0),
+    FieldSpec::flag("book", 0x34E, 8, 0),
+    FieldSpec::flag("somaria", 0x350, 8, 0),
+    FieldSpec::flag("byrna", 0x351, 8, 0),
+    FieldSpec::flag("cape", 0x352, 8, 0),
+    FieldSpec::number("mirror", 0x353, 8, 0),
+    FieldSpec::number("gloves", 0x354, 8, 0),
+    FieldSpec::flag("boots", 0x355, 8, 0),
+    FieldSpec::flag("flippers", 0x356, 8, 0),
+    FieldSpec::flag("moon pearl", 0x357, 8, 0),
+    FieldSpec::number("sword", 0x359, 8, 0),
+    FieldSpec::number("shield", 0x35A, 8, 0),
+    FieldSpec::number("mail", 0x35B, 8, 0),
+    FieldSpec::number("bottle 1", 0x35C, 8, 0),
+    FieldSpec::number("bottle 2", 0x35D, 8, 0),
+    FieldSpec::number("bottle 3", 0x35E, 8, 0),
+    FieldSpec::number("bottle 4", 0x35F, 8, 0),
+    FieldSpec::flag("green pendant", 0x374, 1, 2),
+    FieldSpec::flag("blue pendant", 0x374, 1, 1),
+    FieldSpec::flag("red pendant", 0x374, 1, 0),
+    FieldSpec::flag("crystal 1", 0x37A, 1, 1),
+    FieldSpec::flag("crystal 2", 0x37A, 1, 4),
+    FieldSpec::flag("crystal 3", 0x37A, 1, 6),
+    FieldSpec::flag("crystal 4", 0x37A, 1, 5),
+    FieldSpec::flag("crystal 5", 0x37A, 1, 2),
+    FieldSpec::flag("crystal 6", 0x37A, 1, 0),
+    FieldSpec::flag("crystal 7", 0x37A, 1, 3),
+    FieldSpec::number("follower", 0x3CC, 8, 0),
+];
+
+/// Triforce pieces collected in triforce hunt seeds.
+pub const GOAL_ITEMS: FieldSpec = FieldSpec::number("goal items", 0x418, 8, 0);
+
+/// The values `read_equipment` computes from the swap inventory, bottles and
+/// capacity upgrades.
+pub const DERIVED_EQUIPMENT: &[&str] = &[
+    "bottles",
+    "bow",
+    "silver arrows",
+    "mushroom",
+    "mushroom turned in",
+    "flute",
+    "blue boomerang",
+    "red boomerang",
+    "powder",
+    "shovel",
+    "progressive bows",
+    "max hearts",
+    "max bombs",
+    "max arrows",
+    "current hearts",
+    "magic multiplier",
+    "held keys",
+];
+
+/// Whether `read_equipment` returns a value called `name`.
+pub fn is_equipment(name: &str) -> bool {
+    DERIVED_EQUIPMENT.contains(&name) || find_field(EQUIPMENT_FIELDS, name).is_some()
+}
+
+pub fn read_equipment(sram: &[u8], validate: bool) -> Result<Map<&'static str, Z3REquip>> {
+    read_equipment_from(sram, validate, EQUIPMENT_FIELDS)
+}
+
+/// Like `read_equipment` but decodes `fields` instead of the built-in table.
+/// The swap inventory and capacities are always included.
+#[rustfmt::skip]
+pub fn read_equipment_from<'a>(sram: &[u8], validate: bool, fields: &'a [FieldSpec]) -> Result<Map<&'a str, Z3REquip>> {
+    if validate {
+        validate_sram(sram)?;
+    }
+    let mut sram_equip: Map<&str, Z3REquip> = Map::new();
+    for field in fields {
+        sram_equip.insert(field.name.as_ref(), get_equipment(sram, field)?);
+    }
+
+    let bottle_count: u32 = ["bottle 1", "bottle 2", "bottle 3", "bottle 4"]
         .iter()
-        .fold(0u32, |c, b| if *b != 0u32 {c + 1u32} else {c});
-    sram_equip.insert("bottle 1", bottle_1);
-    sram_equip.insert("bottle 2", bottle_2);
-    sram_equip.insert("bottle 3", bottle_3);
-    sram_equip.insert("bottle 4", bottle_4);
+        .filter_map(|b| sram_equip.get(b))
+        .fold(0u32, |c, b| if b.has() {c + 1u32} else {c});
     sram_equip.insert("bottles", Z3REquip::Number(bottle_count));
 
-    let mushroom_current = get_equipment(&mut cur, 0x38C, 1, 5, true)?;
-    let mushroom_past = get_equipment(&mut cur, 0x38C, 1, 3, true)?;
-    let flute_inactive = get_equipment(&mut cur, 0x38C, 1, 1, true)?;
-    let flute_active = get_equipment(&mut cur, 0x38C, 1, 0, true)?;
-    let bow = get_equipment(&mut cur, 0x340, 8, 0, false)?;
-    let bow_inv = get_equipment(&mut cur, 0x38E, 1, 7, true)?;
-    let silver_bow_inv = get_equipment(&mut cur, 0x38E, 1, 6, true)?;
-    let second_prog_bow_inv = get_equipment(&mut cur, 0x38E, 1, 5, true)?;
-    sram_equip.insert("bow", Z3REquip::Has(bow_inv.has() || second_prog_bow_inv.has() || bow.has()));
-    sram_equip.insert("silver arrows", Z3REquip::Has(silver_bow_inv.has() || bow.value() == 0x03 || bow.value() == 0x04));
-    sram_equip.insert("mushroom", Z3REquip::Has(mushroom_current.has() || mushroom_past.has()));
-    sram_equip.insert("mushroom turned in", Z3REquip::Has(mushroom_past.has()));
-    sram_equip.insert("flute", Z3REquip::Has(flute_inactive.has() || flute_active.has()));
-    sram_equip.insert("blue boomerang", get_equipment(&mut cur, 0x38C, 1, 7, true)?);
-    sram_equip.insert("red boomerang", get_equipment(&mut cur, 0x38C, 1, 6, true)?);
-    sram_equip.insert("powder", get_equipment(&mut cur, 0x38C, 1, 4, true)?);
-    sram_equip.insert("shovel", get_equipment(&mut cur, 0x38C, 1, 2, true)?);
-
-    sram_equip.insert("green pendant", get_equipment(&mut cur, 0x374, 1, 2, true)?);
-    sram_equip.insert("blue pendant", get_equipment(&mut cur, 0x374, 1, 1, true)?);
-    sram_equip.insert("red pendant", get_equipment(&mut cur, 0x374, 1, 0, true)?);
-    sram_equip.insert("crystal 1", get_equipment(&mut cur, 0x37A, 1, 1, true)?);
-    sram_equip.insert("crystal 2", get_equipment(&mut cur, 0x37A, 1, 4, true)?);
-    sram_equip.insert("crystal 3", get_equipment(&mut cur, 0x37A, 1, 6, true)?);
-    sram_equip.insert("crystal 4", get_equipment(&mut cur, 0x37A, 1, 5, true)?);
-    sram_equip.insert("crystal 5", get_equipment(&mut cur, 0x37A, 1, 2, true)?);
-    sram_equip.insert("crystal 6", get_equipment(&mut cur, 0x37A, 1, 0, true)?);
-    sram_equip.insert("crystal 7", get_equipment(&mut cur, 0x37A, 1, 3, true)?);
-    
-    sram_equip.insert("follower", get_equipment(&mut cur, 0x3CC, 8, 0, false)?);
-    
+    let swap = read_swap_inventory(sram, false)?;
+    sram_equip.insert("bow", Z3REquip::Has(swap.owns(SwapItem::Bow)));
+    sram_equip.insert("silver arrows", Z3REquip::Has(swap.owns(SwapItem::SilverBow)));
+    sram_equip.insert("mushroom", Z3REquip::Has(swap.owns(SwapItem::Mushroom)));
+    sram_equip.insert("mushroom turned in", Z3REquip::Has(swap.mushroom() == MushroomState::TurnedIn));
+    sram_equip.insert("flute", Z3REquip::Has(swap.owns(SwapItem::Flute)));
+    sram_equip.insert("blue boomerang", Z3REquip::Has(swap.owns(SwapItem::BlueBoomerang)));
+    sram_equip.insert("red boomerang", Z3REquip::Has(swap.owns(SwapItem::RedBoomerang)));
+    sram_equip.insert("powder", Z3REquip::Has(swap.owns(SwapItem::MagicPowder)));
+    sram_equip.insert("shovel", Z3REquip::Has(swap.owns(SwapItem::Shovel)));
+    sram_equip.insert("progressive bows", Z3REquip::Number(swap.progressive_bows()));
+
+    let capacity = read_capacity(sram, false)?;
+    sram_equip.insert("max hearts", Z3REquip::Number(capacity.max_hearts));
+    sram_equip.insert("max bombs", Z3REquip::Number(capacity.max_bombs));
+    sram_equip.insert("max arrows", Z3REquip::Number(capacity.max_arrows));
+    sram_equip.insert("current hearts", Z3REquip::Number(capacity.current_hearts.hearts));
+    sram_equip.insert("magic multiplier", Z3REquip::Number(capacity.magic_multiplier));
+    sram_equip.insert("held keys", Z3REquip::Number(capacity.held_keys));
+
     Ok(sram_equip)
 }
 
-fn get_equipment(
-    cur: &mut Cursor<&[u8]>,
-    offset: u64,
-    bits: u32,
-    shift: u32,
-    boolean: bool,
-) -> Result<Z3REquip> {
-    cur.set_position(offset);
-    let bytes: f32 = ((bits as f32 + shift as f32) / 8f32).ceil();
-    let mut value = match bytes as u8 {
-        1 => cur.read_u8().unwrap() as u32,
-        2 => cur.read_u16::<LittleEndian>().unwrap() as u32,
-        _ => return Err(anyhow!("Tried reading more than two bytes at {}", offset)),
-    };
-    value >>= shift;
-    value &= bitmask(bits);
-
-    match boolean {
-        true => match value {
+/// Decodes a single field into an equipment value.
+pub fn get_equipment(sram: &[u8], field: &FieldSpec) -> Result<Z3REquip> {
+    let value = read_field(sram, field)?;
+
+    match field.kind {
+        FieldKind::Flag => match value {
             0 => Ok(Z3REquip::Has(false)),
             1 => Ok(Z3REquip::Has(true)),
-            _ => Err(anyhow!("Expected boolean equipment value")),
+            _ => Err(Error::InvalidField(format!(
+                "Expected boolean equipment value for {}",
+                field.name
+            ))),
         },
-        false => Ok(Z3REquip::Number(value)),
+        _ => Ok(Z3REquip::Number(value)),
+    }
+}
+
+/// The Y-items that share an inventory slot and can be swapped between with
+/// the randomizer's item swap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SwapItem {
+    Bow,
+    SilverBow,
+    BlueBoomerang,
+    RedBoomerang,
+    Mushroom,
+    MagicPowder,
+    Shovel,
+    Flute,
+}
+
+/// The four Y-slots that can hold more than one item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SwapSlot {
+    Bow,
+    Boomerang,
+    Mushroom,
+    Flute,
+}
+
+/// Retro mode buys arrows with rupees, so the bow byte at 0x340 never
+/// reflects an arrow count and arrows are usable whenever the player has
+/// rupees.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArrowMode {
+    Normal,
+    Retro,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MushroomState {
+    Missing,
+    Held,
+    TurnedIn,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FluteState {
+    Missing,
+    Inactive,
+    Active,
+}
+
+/// What the player owns versus what is equipped in each swappable Y-slot.
+///
+/// Ownership comes from the randomizer's inventory tracking bits at 0x38C
+/// (boomerangs, mushroom, powder, shovel and flute) and 0x38E (bow, silver
+/// bow and second progressive bow). The equipped item is the vanilla item
+/// byte for that slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapInventory {
+    items: u8,
+    bows: u8,
+    bow: u8,
+    boomerang: u8,
+    mushroom: u8,
+    flute: u8,
+    rupees: u16,
+}
+
+impl SwapInventory {
+    pub fn owns(&self, item: SwapItem) -> bool {
+        match item {
+            SwapItem::Bow => self.bows & 0xA0 != 0 || self.bow != 0,
+            SwapItem::SilverBow => self.bows & 0x40 != 0 || self.bow == 0x03 || self.bow == 0x04,
+            SwapItem::BlueBoomerang => self.items & 0x80 != 0,
+            SwapItem::RedBoomerang => self.items & 0x40 != 0,
+            SwapItem::Mushroom => self.mushroom() != MushroomState::Missing,
+            SwapItem::MagicPowder => self.items & 0x10 != 0,
+            SwapItem::Shovel => self.items & 0x04 != 0,
+            SwapItem::Flute => self.flute() != FluteState::Missing,
+        }
+    }
+
+    pub fn equipped(&self, slot: SwapSlot) -> Option<SwapItem> {
+        match slot {
+            SwapSlot::Bow => match self.bow {
+                0x01 | 0x02 => Some(SwapItem::Bow),
+                0x03 | 0x04 => Some(SwapItem::SilverBow),
+                _ => None,
+            },
+            SwapSlot::Boomerang => match self.boomerang {
+                0x01 => Some(SwapItem::BlueBoomerang),
+                0x02 => Some(SwapItem::RedBoomerang),
+                _ => None,
+            },
+            SwapSlot::Mushroom => match self.mushroom {
+                0x01 => Some(SwapItem::Mushroom),
+                0x02 => Some(SwapItem::MagicPowder),
+                _ => None,
+            },
+            SwapSlot::Flute => match self.flute {
+                0x01 => Some(SwapItem::Shovel),
+                0x02 | 0x03 => Some(SwapItem::Flute),
+                _ => None,
+            },
+        }
+    }
+
+    /// Every owned item that can go in `slot`, in menu order.
+    pub fn owned_in(&self, slot: SwapSlot) -> Vec<SwapItem> {
+        let candidates: [SwapItem; 2] = match slot {
+            SwapSlot::Bow => [SwapItem::Bow, SwapItem::SilverBow],
+            SwapSlot::Boomerang => [SwapItem::BlueBoomerang, SwapItem::RedBoomerang],
+            SwapSlot::Mushroom => [SwapItem::Mushroom, SwapItem::MagicPowder],
+            SwapSlot::Flute => [SwapItem::Shovel, SwapItem::Flute],
+        };
+        candidates
+            .iter()
+            .copied()
+            .filter(|i| self.owns(*i))
+            .collect()
+    }
+
+    /// Number of progressive bows found. The second progressive bow has its
+    /// own tracking bit because silvers can be found before the first bow.
+    pub fn progressive_bows(&self) -> u32 {
+        (self.bows >> 7) as u32 + ((self.bows >> 5) & 0x01) as u32
+    }
+
+    /// Whether the equipped bow can fire. In normal mode this is the arrow
+    /// flag in the bow byte, in retro mode it depends on having rupees.
+    pub fn can_shoot(&self, mode: ArrowMode) -> bool {
+        match mode {
+            ArrowMode::Normal => self.bow == 0x02 || self.bow == 0x04,
+            ArrowMode::Retro => self.bow != 0 && self.rupees > 0,
+        }
+    }
+
+    pub fn mushroom(&self) -> MushroomState {
+        if self.items & 0x20 != 0 {
+            MushroomState::Held
+        } else if self.items & 0x08 != 0 {
+            MushroomState::TurnedIn
+        } else {
+            MushroomState::Missing
+        }
+    }
+
+    pub fn flute(&self) -> FluteState {
+        if self.items & 0x01 != 0 {
+            FluteState::Active
+        } else if self.items & 0x02 != 0 {
+            FluteState::Inactive
+        } else {
+            FluteState::Missing
+        }
+    }
+}
+
+pub fn read_swap_inventory(sram: &[u8], validate: bool) -> Result<SwapInventory> {
+    if validate {
+        validate_sram(sram)?;
+    }
+    if sram.len() < 0x500 {
+        return Err(Error::OutOfBounds { offset: sram.len() });
+    }
+
+    Ok(SwapInventory {
+        items: sram[0x38C],
+        bows: sram[0x38E],
+        bow: sram[0x340],
+        boomerang: sram[0x341],
+        mushroom: sram[0x344],
+        flute: sram[0x34C],
+        rupees: LittleEndian::read_u16(&sram[0x362..0x364]),
+    })
+}
+
+/// Health in whole hearts plus quarter hearts. The game stores health in
+/// eighths of a heart and only ever changes it in steps of two.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hearts {
+    pub hearts: u32,
+    pub quarters: u32,
+}
+
+impl Hearts {
+    fn from_units(units: u8) -> Self {
+        Hearts {
+            hearts: units as u32 / 8,
+            quarters: (units as u32 % 8) / 2,
+        }
+    }
+}
+
+impl fmt::Display for Hearts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.quarters {
+            0 => write!(f, "{}", self.hearts),
+            q => write!(f, "{} {}/4", self.hearts, q),
+        }
+    }
+}
+
+/// Capacities derived with the randomizer's rules: bomb and arrow upgrades
+/// are stored as the amount added to the base 10 bombs and 30 arrows.
+///
+/// The magic meter always holds 128 units. Magic consumption upgrades halve
+/// what each use costs instead, so `magic_multiplier` is how many times
+/// further a full meter goes: 1, 2 or 4.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capacity {
+    pub max_hearts: u32,
+    pub current_hearts: Hearts,
+    pub max_bombs: u32,
+    pub max_arrows: u32,
+    pub max_magic: u32,
+    pub magic_multiplier: u32,
+    pub held_keys: u32,
+}
+
+pub fn read_capacity(sram: &[u8], validate: bool) -> Result<Capacity> {
+    if validate {
+        validate_sram(sram)?;
+    }
+    if sram.len() < 0x500 {
+        return Err(Error::OutOfBounds { offset: sram.len() });
+    }
+
+    // Per-dungeon keys live at 0x37C..0x38A. Sewers and Hyrule Castle share
+    // a key count, so 0x37C is skipped to avoid counting those keys twice.
+    let held_keys: u32 = sram[0x37D..0x38A].iter().map(|k| *k as u32).sum();
+
+    Ok(Capacity {
+        max_hearts: sram[0x36C] as u32 / 8,
+        current_hearts: Hearts::from_units(sram[0x36D]),
+        max_bombs: 10 + sram[0x370] as u32,
+        max_arrows: 30 + sram[0x371] as u32,
+        max_magic: 0x80,
+        magic_multiplier: 1 << (sram[0x37B] & 0x03).min(2),
+        held_keys,
+    })
+}
+
+/// An equipment value the way the bindings present it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum DisplayValue {
+    Value(Z3REquip),
+    /// The name of an upgrade, bottle or follower, or `None` if there isn't one.
+    Name(Option<String>),
+}
+
+/// Names the values of upgrades, bottles and the follower with the `map_*`
+/// functions and passes everything else through unchanged.
+pub fn display_value(name: &str, value: Z3REquip) -> DisplayValue {
+    let v = value.value();
+    match name {
+        "mirror" => DisplayValue::Name(map_mirror(v)),
+        "sword" => DisplayValue::Name(map_sword(v)),
+        "shield" => DisplayValue::Name(map_shield(v)),
+        "mail" => DisplayValue::Name(Some(map_mail(v))),
+        "gloves" => DisplayValue::Name(map_gloves(v)),
+        "magic consumption" => DisplayValue::Name(Some(map_magic_consumption(v))),
+        "bottle 1" | "bottle 2" | "bottle 3" | "bottle 4" => {
+            DisplayValue::Name(map_bottle_contents(v))
+        }
+        "follower" => DisplayValue::Name(map_follower(v)),
+        _ => DisplayValue::Value(value),
     }
 }
 
@@ -251,3 +541,83 @@ pub fn map_follower(v: u32) -> Option<String> {
         _ => Some("Unknown Follower".to_string()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{blank, edited};
+
+    fn swap(edits: &[(usize, u8)]) -> SwapInventory {
+        read_swap_inventory(&edited(edits), false).unwrap()
+    }
+
+    #[test]
+    fn owned_and_equipped_swap_items_differ() {
+        // Both boomerangs and the shovel owned, red boomerang equipped
+        let inventory = swap(&[(0x38C, 0xC4), (0x341, 0x02)]);
+        assert_eq!(
+            inventory.owned_in(SwapSlot::Boomerang),
+            [SwapItem::BlueBoomerang, SwapItem::RedBoomerang]
+        );
+        assert_eq!(
+            inventory.equipped(SwapSlot::Boomerang),
+            Some(SwapItem::RedBoomerang)
+        );
+        assert!(inventory.owns(SwapItem::Shovel));
+        assert_eq!(inventory.equipped(SwapSlot::Flute), None);
+        assert!(inventory.owned_in(SwapSlot::Bow).is_empty());
+    }
+
+    #[test]
+    fn mushroom_and_flute_states() {
+        assert_eq!(swap(&[]).mushroom(), MushroomState::Missing);
+        assert_eq!(swap(&[(0x38C, 0x20)]).mushroom(), MushroomState::Held);
+        let turned_in = swap(&[(0x38C, 0x08)]);
+        assert_eq!(turned_in.mushroom(), MushroomState::TurnedIn);
+        assert!(turned_in.owns(SwapItem::Mushroom));
+
+        assert_eq!(swap(&[]).flute(), FluteState::Missing);
+        assert_eq!(swap(&[(0x38C, 0x02)]).flute(), FluteState::Inactive);
+        assert_eq!(swap(&[(0x38C, 0x01)]).flute(), FluteState::Active);
+    }
+
+    #[test]
+    fn silvers_before_bow_and_progressive_bows() {
+        let silvers_only = swap(&[(0x38E, 0x40)]);
+        assert!(silvers_only.owns(SwapItem::SilverBow));
+        assert!(!silvers_only.owns(SwapItem::Bow));
+        assert_eq!(silvers_only.progressive_bows(), 0);
+
+        let both = swap(&[(0x38E, 0xA0), (0x340, 0x04)]);
+        assert_eq!(both.progressive_bows(), 2);
+        assert_eq!(both.equipped(SwapSlot::Bow), Some(SwapItem::SilverBow));
+        assert!(both.can_shoot(ArrowMode::Normal));
+        assert!(!both.can_shoot(ArrowMode::Retro));
+        assert!(swap(&[(0x340, 0x01), (0x362, 5)]).can_shoot(ArrowMode::Retro));
+    }
+
+    #[test]
+    fn magic_meter_is_always_128() {
+        let mut sram = blank();
+        for (consumption, multiplier) in [(0, 1), (1, 2), (2, 4)] {
+            sram[0x37B] = consumption;
+            let capacity = read_capacity(&sram, false).unwrap();
+            assert_eq!(capacity.max_magic, 128);
+            assert_eq!(capacity.magic_multiplier, multiplier);
+            let equipment = read_equipment(&sram, false).unwrap();
+            assert_eq!(equipment["magic multiplier"], Z3REquip::Number(multiplier));
+        }
+    }
+
+    #[test]
+    fn current_hearts_are_whole_hearts() {
+        // 3 3/4 hearts in eighths
+        let mut sram = blank();
+        sram[0x36D] = 30;
+        let capacity = read_capacity(&sram, false).unwrap();
+        assert_eq!(capacity.current_hearts.to_string(), "3 3/4");
+        let equipment = read_equipment(&sram, false).unwrap();
+        assert_eq!(equipment["current hearts"], Z3REquip::Number(3));
+        assert!(!equipment.contains_key("max magic"));
+    }
+}
@@ -0,0 +1,101 @@
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use crate::prelude::*;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Everything that can go wrong reading or writing a file. Variants that
+/// carry a name or message need the `alloc` feature and I/O errors need `std`.
+#[derive(Debug)]
+pub enum Error {
+    /// The file isn't 32768 bytes.
+    InvalidSize,
+    /// The checksum validity word or the randomizer's file marker is wrong.
+    InvalidFile,
+    /// The ROM name doesn't start with VT or ER.
+    InvalidRomName,
+    /// The stored inverse checksum doesn't match the file.
+    InvalidChecksum,
+    /// A read went past the end of a buffer shorter than a full file.
+    OutOfBounds { offset: usize },
+    /// The file name tiles don't decode to characters.
+    InvalidFileName,
+    /// A file name longer than the 12 characters the game stores.
+    FileNameTooLong(usize),
+    /// A file name character the game's font doesn't have.
+    UnencodableCharacter { character: char, position: usize },
+    /// Meta values such as the ROM name have no numeric value.
+    NotANumber,
+    /// A field spec that can't be decoded, or a value it can't hold.
+    #[cfg(feature = "alloc")]
+    InvalidField(String),
+    #[cfg(feature = "alloc")]
+    UnknownStat(String),
+    #[cfg(feature = "alloc")]
+    UnknownEquipment(String),
+    /// A layout file that couldn't be parsed or has an unknown format.
+    #[cfg(feature = "alloc")]
+    InvalidLayout(String),
+    /// A ROM title prefix that isn't VT or ER.
+    #[cfg(feature = "alloc")]
+    UnknownGenerator(String),
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidSize => write!(f, "Validation Error: Unexpected file size"),
+            Self::InvalidFile => write!(f, "Validation Error: Invalid file"),
+            Self::InvalidRomName => write!(f, "Validation Error: Invalid ROM name"),
+            Self::InvalidChecksum => write!(f, "Validation Error: Invalid checksum"),
+            Self::OutOfBounds { offset } => {
+                write!(f, "Tried reading past the end of the SRAM at {:#X}", offset)
+            }
+            Self::InvalidFileName => write!(f, "Invalid File Name"),
+            Self::FileNameTooLong(len) => {
+                write!(f, "Invalid File Name: {} characters is longer than 12", len)
+            }
+            Self::UnencodableCharacter {
+                character,
+                position,
+            } => write!(
+                f,
+                "Invalid File Name: '{}' at position {} can't be encoded",
+                character, position
+            ),
+            Self::NotANumber => write!(f, "Can't convert non-numeric Z3Rstat to u32"),
+            #[cfg(feature = "alloc")]
+            Self::InvalidField(message) => write!(f, "{}", message),
+            #[cfg(feature = "alloc")]
+            Self::UnknownStat(name) => write!(f, "Unknown stat {}", name),
+            #[cfg(feature = "alloc")]
+            Self::UnknownEquipment(name) => write!(f, "Unknown equipment {}", name),
+            #[cfg(feature = "alloc")]
+            Self::InvalidLayout(message) => write!(f, "Invalid layout: {}", message),
+            #[cfg(feature = "alloc")]
+            Self::UnknownGenerator(prefix) => write!(f, "Unknown generator {}", prefix),
+            #[cfg(feature = "std")]
+            Self::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
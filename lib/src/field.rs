@@ -0,0 +1,208 @@
+use crate::{
+    bitmask,
+    error::{Error, Result},
+    prelude::*,
+};
+
+// Every field has to be inside a full 32768 byte file
+const SRAM_SIZE: usize = 0x8000;
+
+/// How a field's raw value should be presented.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum FieldKind {
+    /// A plain counter or item byte.
+    Number,
+    /// A count out of `max`, such as locations checked in a dungeon.
+    Fraction,
+    /// A 32 bit frame counter.
+    Time,
+    /// A single yes/no value. Reading anything other than 0 or 1 is an error.
+    Flag,
+}
+
+/// Where a value lives in SRAM and how to decode it: read enough little
+/// endian bytes at `offset` to cover `bits + shift` bits, shift right by
+/// `shift` and mask off `bits` bits.
+///
+/// Layout files use `label`, `width` and `type` for `name`, `bits` and
+/// `kind`, and accept offsets as numbers or hex strings.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldSpec {
+    #[cfg_attr(feature = "serde", serde(rename = "label", alias = "name"))]
+    pub name: Cow<'static, str>,
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_offset"))]
+    pub offset: usize,
+    #[cfg_attr(feature = "serde", serde(rename = "width", alias = "bits"))]
+    pub bits: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub shift: u32,
+    #[cfg_attr(feature = "serde", serde(rename = "type", alias = "kind"))]
+    pub kind: FieldKind,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max: Option<u32>,
+}
+
+impl FieldSpec {
+    pub const fn new(
+        name: &'static str,
+        offset: usize,
+        bits: u32,
+        shift: u32,
+        kind: FieldKind,
+        max: Option<u32>,
+    ) -> Self {
+        FieldSpec {
+            name: Cow::Borrowed(name),
+            offset,
+            bits,
+            shift,
+            kind,
+            max,
+        }
+    }
+
+    pub const fn number(name: &'static str, offset: usize, bits: u32, shift: u32) -> Self {
+        Self::new(name, offset, bits, shift, FieldKind::Number, None)
+    }
+
+    pub const fn fraction(
+        name: &'static str,
+        offset: usize,
+        bits: u32,
+        shift: u32,
+        max: u32,
+    ) -> Self {
+        Self::new(name, offset, bits, shift, FieldKind::Fraction, Some(max))
+    }
+
+    pub const fn time(name: &'static str, offset: usize) -> Self {
+        Self::new(name, offset, 32, 0, FieldKind::Time, None)
+    }
+
+    pub const fn flag(name: &'static str, offset: usize, bits: u32, shift: u32) -> Self {
+        Self::new(name, offset, bits, shift, FieldKind::Flag, None)
+    }
+
+    pub fn read(&self, sram: &[u8]) -> Result<u32> {
+        read_field(sram, self)
+    }
+
+    /// Checks that the field can be decoded: it must be 1 to 32 bits wide
+    /// including the shift, lie inside a 32768 byte file, and fractions need
+    /// a max.
+    pub fn check(&self) -> Result<()> {
+        let width = self.bits.saturating_add(self.shift);
+        if self.bits == 0 || width > 32 {
+            return Err(Error::InvalidField(format!(
+                "Invalid field {}: {} bits shifted by {} doesn't fit in 32 bits",
+                self.name, self.bits, self.shift
+            )));
+        }
+        let bytes = (width as usize).div_ceil(8);
+        if self
+            .offset
+            .checked_add(bytes)
+            .is_none_or(|end| end > SRAM_SIZE)
+        {
+            return Err(Error::InvalidField(format!(
+                "Invalid field {}: offset {:#X} is past the end of the SRAM",
+                self.name, self.offset
+            )));
+        }
+        if self.kind == FieldKind::Fraction && self.max.is_none() {
+            return Err(Error::InvalidField(format!(
+                "Invalid field {}: fractions need a max",
+                self.name
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Decodes the raw value of a field.
+pub fn read_field(sram: &[u8], field: &FieldSpec) -> Result<u32> {
+    let width = field.bits.saturating_add(field.shift);
+    let bytes = (width as usize).div_ceil(8);
+    if bytes == 0 || bytes > 4 {
+        return Err(Error::InvalidField(format!(
+            "Tried reading {} bytes for {} at {:#X}",
+            bytes, field.name, field.offset
+        )));
+    }
+    let raw = field
+        .offset
+        .checked_add(bytes)
+        .and_then(|end| sram.get(field.offset..end))
+        .ok_or(Error::OutOfBounds {
+            offset: field.offset,
+        })?;
+    let value = raw.iter().rev().fold(0u64, |v, b| (v << 8) | *b as u64);
+
+    Ok(((value >> field.shift) as u32) & bitmask(field.bits))
+}
+
+pub fn find_field<'a>(fields: &'a [FieldSpec], name: &str) -> Option<&'a FieldSpec> {
+    fields.iter().find(|f| f.name == name)
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_offset<'de, D>(deserializer: D) -> core::result::Result<usize, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::{de::Error, Deserialize};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Offset {
+        Number(usize),
+        Text(String),
+    }
+
+    match Offset::deserialize(deserializer)? {
+        Offset::Number(n) => Ok(n),
+        Offset::Text(t) => {
+            let t = t.trim();
+            match t.strip_prefix("0x").or_else(|| t.strip_prefix("0X")) {
+                Some(hex) => usize::from_str_radix(hex, 16),
+                None => t.parse::<usize>(),
+            }
+            .map_err(|_| D::Error::custom(format!("invalid offset \"{}\"", t)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_shifted_bits_across_bytes() {
+        let sram = [0x00, 0xF0, 0x0F];
+        let field = FieldSpec::number("test", 1, 8, 4);
+        assert_eq!(read_field(&sram, &field).unwrap(), 0xFF);
+    }
+
+    #[test]
+    fn rejects_offsets_past_the_end() {
+        let field = FieldSpec::number("test", usize::MAX, 8, 0);
+        assert!(matches!(
+            read_field(&[0; 16], &field),
+            Err(Error::OutOfBounds { .. })
+        ));
+        assert!(field.check().is_err());
+        assert!(FieldSpec::number("test", 0x7FFF, 16, 0).check().is_err());
+        assert!(FieldSpec::number("test", 0x7FFE, 16, 0).check().is_ok());
+    }
+
+    #[test]
+    fn rejects_overflowing_widths() {
+        let field = FieldSpec::number("test", 0, u32::MAX, 1);
+        assert!(field.check().is_err());
+        assert!(read_field(&[0; 16], &field).is_err());
+    }
+}
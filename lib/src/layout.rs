@@ -0,0 +1,224 @@
+#[cfg(any(feature = "layout-toml", feature = "layout-json"))]
+use crate::error::Error;
+use crate::{
+    equipment::{read_equipment_from, Z3REquip, EQUIPMENT_FIELDS},
+    error::Result,
+    field::FieldSpec,
+    prelude::*,
+    stats::{read_stats_from, Z3RStat, STAT_FIELDS},
+    Map,
+};
+
+/// The stat and equipment field tables used to parse a file. The built-in
+/// layout is what `read_stats` and `read_equipment` use.
+///
+/// Layout files list fields under `stats` and `equipment`. By default they
+/// are merged into the built-in tables, where a field with the same label
+/// replaces the built-in one. Setting `replace = true` uses only the fields in
+/// the file.
+///
+/// ```toml
+/// [[stats]]
+/// label = "doors opened"
+/// offset = 0x470
+/// width = 8
+/// type = "number"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Layout {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub stats: Vec<FieldSpec>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub equipment: Vec<FieldSpec>,
+}
+
+#[cfg(any(feature = "layout-toml", feature = "layout-json"))]
+#[derive(serde::Deserialize)]
+struct LayoutFile {
+    #[serde(default)]
+    replace: bool,
+    #[serde(flatten)]
+    layout: Layout,
+}
+
+impl Layout {
+    pub fn builtin() -> Self {
+        Layout {
+            stats: STAT_FIELDS.to_vec(),
+            equipment: EQUIPMENT_FIELDS.to_vec(),
+        }
+    }
+
+    /// Adds the fields from `other`, replacing fields with the same name.
+    pub fn merge(&mut self, other: Layout) {
+        merge_fields(&mut self.stats, other.stats);
+        merge_fields(&mut self.equipment, other.equipment);
+    }
+
+    pub fn check(&self) -> Result<()> {
+        self.stats
+            .iter()
+            .chain(self.equipment.iter())
+            .try_for_each(|f| f.check())
+    }
+
+    pub fn read_stats(&self, sram: &[u8], validate: bool) -> Result<Map<&str, Z3RStat>> {
+        read_stats_from(sram, validate, &self.stats)
+    }
+
+    pub fn read_equipment(&self, sram: &[u8], validate: bool) -> Result<Map<&str, Z3REquip>> {
+        read_equipment_from(sram, validate, &self.equipment)
+    }
+
+    #[cfg(feature = "layout-toml")]
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let file: LayoutFile =
+            toml::from_str(text).map_err(|e| Error::InvalidLayout(e.to_string()))?;
+        Self::from_file(file)
+    }
+
+    #[cfg(feature = "layout-json")]
+    pub fn from_json(text: &str) -> Result<Self> {
+        let file: LayoutFile =
+            serde_json::from_str(text).map_err(|e| Error::InvalidLayout(e.to_string()))?;
+        Self::from_file(file)
+    }
+
+    /// Loads a `.toml` or `.json` layout file, depending on which layout
+    /// features are enabled.
+    #[cfg(any(feature = "layout-toml", feature = "layout-json"))]
+    pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            #[cfg(feature = "layout-toml")]
+            Some("toml") => Self::from_toml(&text),
+            #[cfg(feature = "layout-json")]
+            Some("json") => Self::from_json(&text),
+            _ => Err(Error::InvalidLayout(format!(
+                "unsupported file {}",
+                path.display()
+            ))),
+        }
+    }
+
+    #[cfg(any(feature = "layout-toml", feature = "layout-json"))]
+    fn from_file(file: LayoutFile) -> Result<Self> {
+        file.layout.check()?;
+        if file.replace {
+            return Ok(file.layout);
+        }
+        let mut layout = Self::builtin();
+        layout.merge(file.layout);
+
+        Ok(layout)
+    }
+}
+
+fn merge_fields(fields: &mut Vec<FieldSpec>, other: Vec<FieldSpec>) {
+    for field in other {
+        match fields.iter_mut().find(|f| f.name == field.name) {
+            Some(existing) => *existing = field,
+            None => fields.push(field),
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "layout-toml", feature = "layout-json")))]
+mod tests {
+    use super::*;
+    use crate::field::{find_field, FieldKind};
+
+    #[cfg(feature = "layout-toml")]
+    #[test]
+    fn toml_merges_by_label() {
+        let layout = Layout::from_toml(
+            r#"
+            [[stats]]
+            label = "doors opened"
+            offset = "0x470"
+            width = 8
+            type = "number"
+
+            [[stats]]
+            label = "deaths"
+            offset = 0x471
+            width = 4
+            shift = 4
+            type = "number"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(layout.stats.len(), STAT_FIELDS.len() + 1);
+        assert_eq!(layout.equipment, EQUIPMENT_FIELDS);
+        let doors = find_field(&layout.stats, "doors opened").unwrap();
+        assert_eq!((doors.offset, doors.kind), (0x470, FieldKind::Number));
+        let deaths = find_field(&layout.stats, "deaths").unwrap();
+        assert_eq!((deaths.offset, deaths.bits, deaths.shift), (0x471, 4, 4));
+    }
+
+    #[cfg(feature = "layout-toml")]
+    #[test]
+    fn toml_replace_uses_only_the_file() {
+        let layout = Layout::from_toml(
+            r#"
+            replace = true
+
+            [[equipment]]
+            label = "sword"
+            offset = "0x359"
+            width = 8
+            type = "number"
+            "#,
+        )
+        .unwrap();
+
+        assert!(layout.stats.is_empty());
+        assert_eq!(layout.equipment.len(), 1);
+    }
+
+    #[cfg(feature = "layout-json")]
+    #[test]
+    fn json_offsets_are_numbers_or_hex_strings() {
+        let layout = Layout::from_json(
+            r#"{"replace": true, "stats": [
+                {"label": "a", "offset": 1136, "width": 8, "type": "number"},
+                {"label": "b", "offset": " 0X470 ", "width": 8, "type": "number"},
+                {"label": "c", "offset": "1136", "width": 8, "type": "number"}
+            ]}"#,
+        )
+        .unwrap();
+        assert!(layout.stats.iter().all(|f| f.offset == 0x470));
+
+        let bad = r#"{"stats": [{"label": "a", "offset": "0xZZ", "width": 8, "type": "number"}]}"#;
+        assert!(matches!(
+            Layout::from_json(bad),
+            Err(Error::InvalidLayout(_))
+        ));
+    }
+
+    #[cfg(feature = "layout-json")]
+    #[test]
+    fn layouts_are_checked() {
+        let field = |offset: &str, width: u32, shift: u32, kind: &str| {
+            format!(
+                r#"{{"stats": [{{"label": "a", "offset": {}, "width": {}, "shift": {}, "type": "{}"}}]}}"#,
+                offset, width, shift, kind
+            )
+        };
+        assert!(Layout::from_json(&field("0", 8, 0, "number")).is_ok());
+        for bad in [
+            field("0", 0, 0, "number"),
+            field("0", 8, 30, "number"),
+            field(r#""0xFFFFFFFFFFFFFFFF""#, 8, 0, "number"),
+            field("0", 8, 0, "fraction"),
+        ] {
+            assert!(matches!(
+                Layout::from_json(&bad),
+                Err(Error::InvalidField(_))
+            ));
+        }
+    }
+}
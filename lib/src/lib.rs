@@ -1,51 +1,177 @@
-use anyhow::{anyhow, Result};
-use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
-use std::{io::Cursor, str::from_utf8};
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+#[cfg(feature = "alloc")]
+use completion::{read_completion, Completion};
+#[cfg(feature = "alloc")]
+use equipment::{read_equipment, Z3REquip};
+use error::{Error, Result};
+#[cfg(feature = "alloc")]
+use rom::{read_rom_info, RomInfo};
+#[cfg(feature = "alloc")]
+use stats::{read_stats, Z3RStat};
+
+#[cfg(feature = "alloc")]
+pub mod aggregate;
+#[cfg(feature = "alloc")]
+pub mod analytics;
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "alloc")]
+pub mod completion;
+#[cfg(feature = "alloc")]
+pub mod consistency;
+#[cfg(feature = "alloc")]
 pub mod equipment;
+pub mod error;
+#[cfg(feature = "alloc")]
+pub mod field;
+#[cfg(feature = "alloc")]
+pub mod layout;
+#[cfg(feature = "std")]
+pub mod overlay;
+#[cfg(feature = "alloc")]
+pub mod rom;
+#[cfg(feature = "alloc")]
+pub mod splits;
+#[cfg(feature = "alloc")]
 pub mod stats;
+#[cfg(feature = "std")]
+pub mod submissions;
+#[cfg(feature = "alloc")]
+pub mod timeline;
+#[cfg(feature = "alloc")]
+pub mod view;
+#[cfg(feature = "std")]
+pub mod watch;
+#[cfg(feature = "alloc")]
+pub mod writer;
 
-pub fn validate_sram(sram: &[u8]) -> Result<()> {
-    let mut cur = Cursor::new(sram);
+#[cfg(test)]
+pub(crate) mod test_util;
+
+/// The map parsed stats and equipment are returned in. It's a `BTreeMap`
+/// whatever features are enabled, so enabling `std` elsewhere in a dependency
+/// graph doesn't change it, and iterates in key order.
+#[cfg(feature = "alloc")]
+pub type Map<K, V> = alloc::collections::BTreeMap<K, V>;
+
+// The alloc types std's prelude would otherwise provide
+#[cfg(feature = "alloc")]
+mod prelude {
+    pub(crate) use alloc::{
+        borrow::Cow,
+        format,
+        string::{String, ToString},
+        vec::Vec,
+    };
+}
 
+pub fn validate_sram(sram: &[u8]) -> Result<()> {
     // Check the length. 32768 bytes as of v30.0.4, 2019-11-15 ROM build
     if sram.len() != 32768 {
-        return Err(anyhow!("Validation Error: Unexpected file size"));
+        return Err(Error::InvalidSize);
     }
     // Check the checksum validity value and the rando-specific file marker
     // 0x55AA and 0xFF
     let checksum_validity: u16 = LittleEndian::read_u16(&sram[0x3E1..0x3E3]);
     if checksum_validity != 0x55AA || sram[0x4F0] != 0xFF {
-        return Err(anyhow!("Validation Error: Invalid file"));
+        return Err(Error::InvalidFile);
     }
 
-    // Check the first two characters of the rom name for VT or ER
-    let valid_names: [&str; 2] = ["VT", "ER"];
-    let rom_name = &sram[0x2000..0x2002];
-    if valid_names
-        .iter()
-        .any(|&x| x == from_utf8(&rom_name).unwrap())
-        == false
-    {
-        return Err(anyhow!("Validation Error: Invalid ROM name"));
+    // Check the first two characters of the rom name for VT or ER. These are
+    // compared as bytes since the name isn't guaranteed to be UTF-8.
+    if !matches!(&sram[0x2000..0x2002], b"VT" | b"ER") {
+        return Err(Error::InvalidRomName);
     }
 
     // Now we check the SRAM's own "inverse" checksum
-    let mut checksum = 0u16;
-    cur.set_position(0x00);
-    while cur.position() < 0x4FE {
-        let bytes = cur.read_u16::<LittleEndian>()?;
-        checksum = checksum.overflowing_add(bytes).0;
-    }
-    let expected_inv_checksum = 0x5A5Au16.overflowing_sub(checksum).0;
+    let expected_inv_checksum = inverse_checksum(sram)?;
     let inv_checksum: u16 = LittleEndian::read_u16(&sram[0x4FE..0x500]);
     if inv_checksum != expected_inv_checksum {
-        return Err(anyhow!("Validation Error: Invalid checksum"));
+        return Err(Error::InvalidChecksum);
+    }
+
+    Ok(())
+}
+
+/// Everything the crate parses from one file.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ParsedSram {
+    pub rom: Option<RomInfo>,
+    pub stats: Map<&'static str, Z3RStat>,
+    pub equipment: Map<&'static str, Z3REquip>,
+    pub completion: Completion,
+}
+
+#[cfg(feature = "alloc")]
+pub fn parse_sram(sram: &[u8], validate: bool) -> Result<ParsedSram> {
+    if validate {
+        validate_sram(sram)?;
     }
 
-    return Ok(());
+    Ok(ParsedSram {
+        rom: read_rom_info(sram, false).ok(),
+        stats: read_stats(sram, false)?,
+        equipment: read_equipment(sram, false)?,
+        completion: read_completion(sram, false)?,
+    })
+}
+
+// The game sums the first 0x4FE bytes as little endian words and stores
+// whatever brings that sum to 0x5A5A.
+pub(crate) fn inverse_checksum(sram: &[u8]) -> Result<u16> {
+    let words = sram
+        .get(..0x4FE)
+        .ok_or(Error::OutOfBounds { offset: sram.len() })?;
+    let checksum = words
+        .chunks_exact(2)
+        .fold(0u16, |sum, w| sum.wrapping_add(LittleEndian::read_u16(w)));
+
+    Ok(0x5A5Au16.wrapping_sub(checksum))
 }
 
+// Each underworld room has a 16 bit flag word at 0x000: bits 4-9 are opened
+// chests and bit 11 is set once the room's boss is defeated.
+#[cfg(feature = "alloc")]
+pub(crate) fn room_flags(sram: &[u8], room: usize) -> u16 {
+    LittleEndian::read_u16(&sram[room * 2..room * 2 + 2])
+}
+
+#[cfg(feature = "alloc")]
 pub(crate) fn bitmask(bits: u32) -> u32 {
-    (1u32 << bits) - 1u32
+    ((1u64 << bits) - 1u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{blank, fix_checksum};
+
+    #[test]
+    fn validates_blank_file() {
+        assert!(validate_sram(&blank()).is_ok());
+    }
+
+    #[test]
+    fn rejects_non_utf8_rom_name() {
+        let mut sram = blank();
+        sram[0x2000] = 0xFF;
+        fix_checksum(&mut sram);
+        assert!(matches!(validate_sram(&sram), Err(Error::InvalidRomName)));
+    }
+
+    #[test]
+    fn rejects_wrong_size_and_checksum() {
+        assert!(matches!(validate_sram(&[0; 100]), Err(Error::InvalidSize)));
+        let mut sram = blank();
+        sram[0x4FE] ^= 1;
+        assert!(matches!(validate_sram(&sram), Err(Error::InvalidChecksum)));
+    }
 }
@@ -0,0 +1,167 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    equipment::is_equipment,
+    error::{Error, Result},
+    stats::is_stat,
+    ParsedSram,
+};
+
+/// A value to mirror into a text file.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OverlayField {
+    Stat(String),
+    Equipment(String),
+}
+
+impl OverlayField {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Stat(name) | Self::Equipment(name) => name,
+        }
+    }
+
+    /// Checks that the name is one `read_stats` or `read_equipment` returns.
+    pub fn check(&self) -> Result<()> {
+        match self {
+            Self::Stat(name) if !is_stat(name) => Err(Error::UnknownStat(name.clone())),
+            Self::Equipment(name) if !is_equipment(name) => {
+                Err(Error::UnknownEquipment(name.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn file_name(&self) -> String {
+        match self {
+            Self::Stat(name) => format!("stat-{}.txt", name),
+            Self::Equipment(name) => format!("equip-{}.txt", name),
+        }
+    }
+
+    /// The field's `Display` text, or an empty string if `parsed` doesn't
+    /// have it.
+    fn text(&self, parsed: &ParsedSram) -> String {
+        let text = match self {
+            Self::Stat(name) => parsed.stats.get(name.as_str()).map(|s| s.to_string()),
+            Self::Equipment(name) => parsed.equipment.get(name.as_str()).map(|e| e.to_string()),
+        };
+
+        text.unwrap_or_default()
+    }
+}
+
+/// Writes one text file per field for use as OBS text sources. Each file is
+/// named after its field with a `stat-` or `equip-` prefix, e.g.
+/// `stat-total time.txt`, since some stats and equipment share a name.
+///
+/// Values are remembered between updates and a file is only rewritten when
+/// its value changes, so sources aren't reloaded on every save.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Overlay {
+    dir: PathBuf,
+    fields: Vec<OverlayField>,
+    written: HashMap<OverlayField, String>,
+}
+
+impl Overlay {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Overlay {
+            dir: dir.into(),
+            fields: Vec::new(),
+            written: HashMap::new(),
+        }
+    }
+
+    /// Adds a field, failing if it isn't a known stat or equipment name.
+    pub fn with_field(mut self, field: OverlayField) -> Result<Self> {
+        field.check()?;
+        self.fields.push(field);
+        Ok(self)
+    }
+
+    pub fn with_stat(self, name: &str) -> Result<Self> {
+        self.with_field(OverlayField::Stat(name.to_string()))
+    }
+
+    pub fn with_equipment(self, name: &str) -> Result<Self> {
+        self.with_field(OverlayField::Equipment(name.to_string()))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn fields(&self) -> &[OverlayField] {
+        &self.fields
+    }
+
+    /// The path of a field's text file.
+    pub fn path(&self, field: &OverlayField) -> PathBuf {
+        self.dir.join(field.file_name())
+    }
+
+    /// Writes every field whose value changed since the last update and
+    /// returns the paths that were written. The first update writes them all.
+    ///
+    /// Files are written to a temporary file first and renamed into place so
+    /// OBS never reads a half written value.
+    pub fn update(&mut self, parsed: &ParsedSram) -> Result<Vec<PathBuf>> {
+        let mut written: Vec<PathBuf> = Vec::new();
+        for field in self.fields.iter() {
+            let text = field.text(parsed);
+            if self.written.get(field) == Some(&text) {
+                continue;
+            }
+            let path = self.path(field);
+            let tmp = path.with_extension("txt.tmp");
+            fs::write(&tmp, &text)?;
+            fs::rename(&tmp, &path)?;
+            self.written.insert(field.clone(), text);
+            written.push(path);
+        }
+
+        Ok(written)
+    }
+
+    /// Forgets the remembered values so the next update rewrites every file.
+    pub fn reset(&mut self) {
+        self.written.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_unknown_names() {
+        let overlay = Overlay::new("overlay");
+        assert!(matches!(
+            overlay.clone().with_stat("total tiem"),
+            Err(Error::UnknownStat(_))
+        ));
+        assert!(matches!(
+            overlay.clone().with_equipment("swrod"),
+            Err(Error::UnknownEquipment(_))
+        ));
+        assert!(overlay.clone().with_stat("lag time").is_ok());
+        assert!(overlay.with_equipment("progressive bows").is_ok());
+    }
+
+    #[test]
+    fn stats_and_equipment_get_separate_files() {
+        let overlay = Overlay::new("overlay")
+            .with_stat("heart pieces")
+            .and_then(|o| o.with_equipment("heart pieces"))
+            .unwrap();
+        let stat = overlay.path(&overlay.fields()[0]);
+        let equipment = overlay.path(&overlay.fields()[1]);
+        assert_eq!(stat, Path::new("overlay/stat-heart pieces.txt"));
+        assert_eq!(equipment, Path::new("overlay/equip-heart pieces.txt"));
+    }
+}
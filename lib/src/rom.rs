@@ -0,0 +1,256 @@
+use core::fmt;
+
+use crate::{
+    error::{Error, Result},
+    prelude::*,
+    validate_sram, Map,
+};
+
+/// The randomizer that generated the ROM, taken from the first two
+/// characters of the ROM title.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Generator {
+    /// alttpr.com (VT) seeds.
+    Alttpr,
+    /// The entrance randomizer and its door randomizer fork (ER).
+    EntranceRandomizer,
+}
+
+impl Generator {
+    /// The generator whose ROM titles start with `prefix`.
+    pub fn from_prefix(prefix: &str) -> Result<Self> {
+        match prefix {
+            "VT" => Ok(Self::Alttpr),
+            "ER" => Ok(Self::EntranceRandomizer),
+            _ => Err(Error::UnknownGenerator(prefix.to_string())),
+        }
+    }
+
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Self::Alttpr => "VT",
+            Self::EntranceRandomizer => "ER",
+        }
+    }
+}
+
+impl fmt::Display for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.prefix())
+    }
+}
+
+/// Everything encoded in the 21 byte ROM title at 0x2000.
+///
+/// VT titles are `VT` followed by a space, the 10 character seed hash and
+/// an optional build tag. ER titles are `ER`, the first three digits of the
+/// generator version, then the team number and the seed separated by
+/// underscores. Older ER builds left out the team.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RomInfo {
+    pub name: String,
+    pub generator: Generator,
+    pub build: Option<String>,
+    pub hash: Option<String>,
+    pub seed: Option<u64>,
+    pub team: Option<u32>,
+}
+
+impl RomInfo {
+    /// The permalink from the default [`Permalinks`].
+    pub fn permalink(&self) -> Option<String> {
+        Permalinks::default().permalink(self)
+    }
+}
+
+pub fn read_rom_info(sram: &[u8], validate: bool) -> Result<RomInfo> {
+    if validate {
+        validate_sram(sram)?;
+    }
+    if sram.len() < 0x2015 {
+        return Err(Error::OutOfBounds { offset: sram.len() });
+    }
+    let name = String::from_utf8_lossy(&sram[0x2000..0x2015])
+        .trim_end_matches(&[' ', '\0'][..])
+        .to_string();
+
+    match name.get(..2) {
+        Some("VT") => Ok(parse_vt(name)),
+        Some("ER") => Ok(parse_er(name)),
+        _ => Err(Error::InvalidRomName),
+    }
+}
+
+fn parse_vt(name: String) -> RomInfo {
+    let hash = name.get(3..13).map(|h| h.to_string());
+    let build = name
+        .get(13..)
+        .map(|b| b.trim())
+        .filter(|b| !b.is_empty())
+        .map(|b| b.to_string());
+
+    RomInfo {
+        generator: Generator::Alttpr,
+        build,
+        hash,
+        seed: None,
+        team: None,
+        name,
+    }
+}
+
+fn parse_er(name: String) -> RomInfo {
+    let fields: Vec<&str> = name[2..].split('_').collect();
+    let build = Some(fields[0])
+        .filter(|b| !b.is_empty())
+        .map(|b| b.to_string());
+    let (team, seed) = match fields.len() {
+        3 => (fields[1].parse::<u32>().ok(), fields[2].parse::<u64>().ok()),
+        2 => (None, fields[1].parse::<u64>().ok()),
+        _ => (None, None),
+    };
+
+    RomInfo {
+        generator: Generator::EntranceRandomizer,
+        build,
+        hash: None,
+        seed,
+        team,
+        name,
+    }
+}
+
+/// Builds seed permalinks from a URL template per generator. `{hash}` and
+/// `{seed}` in a template are replaced with the ROM's values, and no link is
+/// built if the ROM doesn't have the value a template needs.
+///
+/// The default only knows about alttpr.com. ER seeds have no permalink unless
+/// a template is configured for them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Permalinks {
+    templates: Map<Generator, String>,
+}
+
+impl Permalinks {
+    pub fn new() -> Self {
+        Permalinks {
+            templates: Map::new(),
+        }
+    }
+
+    pub fn with_template(mut self, generator: Generator, template: &str) -> Self {
+        self.templates.insert(generator, template.to_string());
+        self
+    }
+
+    pub fn without(mut self, generator: Generator) -> Self {
+        self.templates.remove(&generator);
+        self
+    }
+
+    /// Sets the template for the generator with the ROM title `prefix`, or
+    /// removes it when `template` is `None`. This is how the bindings take
+    /// templates from callers, keyed by "VT" and "ER".
+    pub fn with_prefix(self, prefix: &str, template: Option<&str>) -> Result<Self> {
+        let generator = Generator::from_prefix(prefix)?;
+        Ok(match template {
+            Some(template) => self.with_template(generator, template),
+            None => self.without(generator),
+        })
+    }
+
+    pub fn permalink(&self, rom: &RomInfo) -> Option<String> {
+        let mut link = self.templates.get(&rom.generator)?.clone();
+        if link.contains("{hash}") {
+            link = link.replace("{hash}", rom.hash.as_ref()?);
+        }
+        if link.contains("{seed}") {
+            link = link.replace("{seed}", &rom.seed?.to_string());
+        }
+
+        Some(link)
+    }
+}
+
+impl Default for Permalinks {
+    fn default() -> Self {
+        Permalinks::new().with_template(Generator::Alttpr, "https://alttpr.com/h/{hash}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{blank, titled};
+
+    fn rom_info(title: &str) -> RomInfo {
+        read_rom_info(&titled(title), false).unwrap()
+    }
+
+    #[test]
+    fn parses_vt_titles() {
+        let rom = rom_info("VT 1234567890");
+        assert_eq!(rom.generator, Generator::Alttpr);
+        assert_eq!(rom.hash.as_deref(), Some("1234567890"));
+        assert_eq!(rom.build, None);
+        assert_eq!(
+            rom.permalink().as_deref(),
+            Some("https://alttpr.com/h/1234567890")
+        );
+
+        let tagged = rom_info("VT ABCDEFGHIJ v31");
+        assert_eq!(tagged.name, "VT ABCDEFGHIJ v31");
+        assert_eq!(tagged.hash.as_deref(), Some("ABCDEFGHIJ"));
+        assert_eq!(tagged.build.as_deref(), Some("v31"));
+    }
+
+    #[test]
+    fn parses_er_titles() {
+        let rom = rom_info("ER400_1_123456789");
+        assert_eq!(rom.generator, Generator::EntranceRandomizer);
+        assert_eq!(rom.build.as_deref(), Some("400"));
+        assert_eq!(rom.team, Some(1));
+        assert_eq!(rom.seed, Some(123456789));
+        assert_eq!(rom.hash, None);
+        assert_eq!(rom.permalink(), None);
+
+        let old = rom_info("ER002_987654321");
+        assert_eq!(old.team, None);
+        assert_eq!(old.seed, Some(987654321));
+
+        let link = Permalinks::new()
+            .with_template(Generator::EntranceRandomizer, "https://example.com/{seed}")
+            .permalink(&old);
+        assert_eq!(link.as_deref(), Some("https://example.com/987654321"));
+    }
+
+    #[test]
+    fn templates_by_prefix() {
+        let rom = rom_info("VT 1234567890");
+        let mirror = Permalinks::default()
+            .with_prefix("VT", Some("https://seeds.example.com/{hash}"))
+            .unwrap();
+        assert_eq!(
+            mirror.permalink(&rom).as_deref(),
+            Some("https://seeds.example.com/1234567890")
+        );
+        let none = Permalinks::default().with_prefix("VT", None).unwrap();
+        assert_eq!(none.permalink(&rom), None);
+        assert!(matches!(
+            Permalinks::default().with_prefix("XX", None),
+            Err(Error::UnknownGenerator(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_other_titles() {
+        let mut sram = blank();
+        sram[0x2000..0x2002].copy_from_slice(b"ZE");
+        assert!(matches!(
+            read_rom_info(&sram, false),
+            Err(Error::InvalidRomName)
+        ));
+    }
+}
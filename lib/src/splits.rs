@@ -0,0 +1,208 @@
+use core::fmt::Write;
+
+use crate::{
+    analytics::Milestone, completion::read_completion, error::Result, prelude::*,
+    timeline::TimelineEvent, validate_sram, view::SramView,
+};
+
+/// A named point in the run, in in-game frames since the start.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Split {
+    pub name: String,
+    pub frame: u32,
+}
+
+/// Splits in the order they happened, for export to LiveSplit or a
+/// spreadsheet. All times are in-game time.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Splits {
+    splits: Vec<Split>,
+}
+
+/// Reads a split for every milestone that was reached and, for finished
+/// runs, a last split at the final time named after how the run ended.
+pub fn read_splits(sram: &[u8], validate: bool) -> Result<Splits> {
+    if validate {
+        validate_sram(sram)?;
+    }
+    let view = SramView::new(sram)?;
+    let mut splits = Splits::new();
+    for milestone in Milestone::ALL.iter() {
+        if let Some(frame) = milestone.frame(&view) {
+            splits.push(milestone.to_string(), frame);
+        }
+    }
+    let completion = read_completion(sram, false)?;
+    if let Some(frame) = completion.final_time() {
+        splits.push(completion.to_string(), frame);
+    }
+
+    Ok(splits)
+}
+
+impl Splits {
+    pub fn new() -> Self {
+        Splits { splits: Vec::new() }
+    }
+
+    /// Adds a split, keeping the list ordered by frame. Splits on the same
+    /// frame stay in the order they were added.
+    pub fn push(&mut self, name: String, frame: u32) {
+        let at = self.splits.partition_point(|s| s.frame <= frame);
+        self.splits.insert(at, Split { name, frame });
+    }
+
+    /// Adds a split for every timeline event.
+    pub fn with_events(mut self, events: &[TimelineEvent]) -> Self {
+        for event in events {
+            self.push(event.kind.to_string(), event.frame);
+        }
+        self
+    }
+
+    pub fn splits(&self) -> &[Split] {
+        &self.splits
+    }
+
+    /// A LiveSplit 1.7 splits file with one segment per split and empty
+    /// speedrun.com metadata. Each split's game time is used as both the
+    /// personal best and the best segment.
+    pub fn to_lss(&self, game: &str, category: &str) -> String {
+        let mut lss = String::new();
+        lss.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        lss.push_str("<Run version=\"1.7.0\">\n");
+        lss.push_str("  <GameIcon />\n");
+        let _ = writeln!(lss, "  <GameName>{}</GameName>", escape_xml(game));
+        let _ = writeln!(
+            lss,
+            "  <CategoryName>{}</CategoryName>",
+            escape_xml(category)
+        );
+        lss.push_str("  <Metadata>\n");
+        lss.push_str("    <Run id=\"\" />\n");
+        lss.push_str("    <Platform usesEmulator=\"False\"></Platform>\n");
+        lss.push_str("    <Region></Region>\n");
+        lss.push_str("    <Variables />\n");
+        lss.push_str("  </Metadata>\n");
+        lss.push_str("  <Offset>00:00:00</Offset>\n");
+        lss.push_str("  <AttemptCount>0</AttemptCount>\n");
+        lss.push_str("  <AttemptHistory />\n");
+        lss.push_str("  <Segments>\n");
+        let mut previous = 0;
+        for split in self.splits.iter() {
+            lss.push_str("    <Segment>\n");
+            let _ = writeln!(lss, "      <Name>{}</Name>", escape_xml(&split.name));
+            lss.push_str("      <Icon />\n");
+            lss.push_str("      <SplitTimes>\n");
+            lss.push_str("        <SplitTime name=\"Personal Best\">\n");
+            let _ = writeln!(
+                lss,
+                "          <GameTime>{}</GameTime>",
+                timespan(split.frame)
+            );
+            lss.push_str("        </SplitTime>\n");
+            lss.push_str("      </SplitTimes>\n");
+            lss.push_str("      <BestSegmentTime>\n");
+            let _ = writeln!(
+                lss,
+                "        <GameTime>{}</GameTime>",
+                timespan(split.frame - previous)
+            );
+            lss.push_str("      </BestSegmentTime>\n");
+            lss.push_str("      <SegmentHistory />\n");
+            lss.push_str("    </Segment>\n");
+            previous = split.frame;
+        }
+        lss.push_str("  </Segments>\n");
+        lss.push_str("  <AutoSplitterSettings />\n");
+        lss.push_str("</Run>\n");
+
+        lss
+    }
+
+    /// One row per split with its time and the length of its segment, both
+    /// as frames and formatted like the time stats.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("name,frame,time,segment frames,segment time\n");
+        let mut previous = 0;
+        for split in self.splits.iter() {
+            let segment = split.frame - previous;
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{}",
+                escape_csv(&split.name),
+                split.frame,
+                clock(split.frame),
+                segment,
+                clock(segment)
+            );
+            previous = split.frame;
+        }
+
+        csv
+    }
+}
+
+// .NET TimeSpan format, which LiveSplit writes with 7 fractional digits
+fn timespan(frames: u32) -> String {
+    let ticks = frames as u64 * 10_000_000 / 60;
+    let seconds = ticks / 10_000_000;
+    format!(
+        "{:02}:{:02}:{:02}.{:07}",
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60,
+        ticks % 10_000_000
+    )
+}
+
+// hh:mm:ss.ff with frames after the point, like the time stats
+fn clock(frames: u32) -> String {
+    let seconds = frames / 60;
+    format!(
+        "{:02}:{:02}:{:02}.{:02}",
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60,
+        frames % 60
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn escape_csv(text: &str) -> String {
+    if text.contains(&[',', '"', '\n'][..]) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_a_livesplit_file() {
+        let mut splits = Splits::new();
+        splits.push("Pendant of Courage".to_string(), 54_000);
+        splits.push("Ganon & Co".to_string(), 216_090);
+        let expected = include_str!("../testdata/splits.lss");
+        assert_eq!(splits.to_lss("A Link to the Past", "No Glitches"), expected);
+    }
+
+    #[test]
+    fn keeps_splits_in_frame_order() {
+        let mut splits = Splits::new();
+        splits.push("b".to_string(), 20);
+        splits.push("a".to_string(), 10);
+        splits.push("c".to_string(), 20);
+        let names: Vec<&str> = splits.splits().iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "c"]);
+    }
+}
@@ -1,9 +1,17 @@
-use anyhow::{anyhow, Result};
-use byteorder::{LittleEndian, ReadBytesExt};
-use std::{collections::HashMap, convert::TryFrom, fmt, io::Cursor, str::from_utf8};
+use byteorder::{ByteOrder, LittleEndian};
+use core::{convert::TryFrom, fmt, iter};
 
-use crate::{bitmask, validate_sram};
+use crate::{
+    error::{Error, Result},
+    field::{find_field, read_field, FieldKind, FieldSpec},
+    prelude::*,
+    rom::{read_rom_info, RomInfo},
+    validate_sram, Map,
+};
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
 pub enum Z3RStat {
     Meta(Option<String>),
     Number(u32),
@@ -12,14 +20,7 @@ pub enum Z3RStat {
 }
 
 impl Z3RStat {
-    fn new_time<T: Into<u64>>(cur: Option<&mut Cursor<&[u8]>>, num: T) -> Self {
-        let value: u32 = match cur {
-            Some(cur) => {
-                cur.set_position(num.into());
-                cur.read_u32::<LittleEndian>().unwrap()
-            }
-            None => num.into() as u32,
-        };
+    pub(crate) fn new_time(value: u32) -> Self {
         let hours: u32 = value / (216000u32);
         let mut rem = value % 216000u32;
         let minutes: u32 = rem / 3600u32;
@@ -46,184 +47,264 @@ impl fmt::Display for Z3RStat {
 }
 
 impl TryFrom<&Z3RStat> for u32 {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(stat: &Z3RStat) -> Result<Self> {
         match stat {
             Z3RStat::Number(n) => Ok(*n),
             Z3RStat::Time(t) => {
                 let time: Vec<u32> = t
-                    .split(|c: char| c.is_numeric() == false)
-                    .map(|x| u32::from_str_radix(x, 10).unwrap())
+                    .split(|c: char| !c.is_numeric())
+                    .map(|x| x.parse::<u32>().unwrap())
                     .collect();
                 Ok((time[0] * 216000u32) + (time[1] * 3600u32) + (time[2] * 60) + time[3])
             }
             Z3RStat::Fraction(f) => {
-                let fraction: Vec<u32> = f
-                    .split("/")
-                    .map(|x| u32::from_str_radix(x, 10).unwrap())
-                    .collect();
+                let fraction: Vec<u32> = f.split("/").map(|x| x.parse::<u32>().unwrap()).collect();
                 Ok(fraction[0])
             }
-            _ => Err(anyhow!("Can't convert non-numeric Z3Rstat to u32")),
+            _ => Err(Error::NotANumber),
         }
     }
 }
 
-#[cfg_attr(rustfmt, rustfmt_skip)]
-pub fn read_stats(sram: &[u8], validate: bool) -> Result<HashMap<&str, Z3RStat>> {
-    if validate == true {
-        validate_sram(&sram)?;
+/// Every stat `read_stats` decodes straight from SRAM. Meta values and
+/// derived stats such as "lag time" are not fields and aren't listed here.
+#[rustfmt::skip]
+pub const STAT_FIELDS: &[FieldSpec] = &[
+    COLLECTION_RATE,
+    CHEST_LOCATIONS,
+    FieldSpec::fraction("y items", 0x421, 5, 3, 27),
+    FieldSpec::fraction("a items", 0x421, 3, 0, 5),
+    FieldSpec::fraction("swords", 0x422, 3, 5, 4),
+    FieldSpec::fraction("shields", 0x422, 2, 3, 3),
+    FieldSpec::fraction("mails", 0x424, 2, 6, 3),
+    FieldSpec::fraction("capacity upgrades", 0x452, 4, 0, 15),
+    FieldSpec::fraction("heart containers", 0x429, 4, 4, 11),
+    FieldSpec::fraction("heart pieces", 0x448, 8, 0, 24),
+    FieldSpec::fraction("maps", 0x428, 4, 4, 12),
+    FieldSpec::fraction("compasses", 0x428, 4, 0, 11),
+    FieldSpec::fraction("small keys", 0x424, 6, 0, 61),
+    FieldSpec::fraction("big keys", 0x427, 4, 4, 12),
+    FieldSpec::fraction("big chests", 0x427, 4, 0, 11),
+    FieldSpec::fraction("pendants", 0x429, 2, 0, 3),
+    FieldSpec::fraction("crystals", 0x422, 3, 0, 7),
+    FieldSpec::fraction("hyrule castle", 0x434, 4, 4, 8),
+    FieldSpec::fraction("eastern palace", 0x436, 3, 0, 6),
+    FieldSpec::fraction("desert palace", 0x435, 3, 5, 6),
+    FieldSpec::fraction("tower of hera", 0x435, 3, 2, 5),
+    FieldSpec::fraction("castle tower", 0x435, 2, 0, 2),
+    FieldSpec::fraction("palace of darkness", 0x434, 4, 0, 14),
+    FieldSpec::fraction("swamp palace", 0x439, 4, 0, 10),
+    FieldSpec::fraction("skull woods", 0x437, 4, 4, 8),
+    FieldSpec::fraction("thieves town", 0x437, 4, 0, 8),
+    FieldSpec::fraction("ice palace", 0x438, 4, 4, 8),
+    FieldSpec::fraction("misery mire", 0x438, 4, 0, 8),
+    FieldSpec::fraction("turtle rock", 0x439, 4, 4, 12),
+    FieldSpec::fraction("ganons tower", 0x436, 5, 3, 27),
+    FieldSpec::fraction("ganons tower big key", 0x42A, 5, 0, 22),
+    FieldSpec::fraction("swordless bosses", 0x452, 4, 4, 13),
+    FieldSpec::fraction("fighter sword bosses", 0x426, 4, 4, 13),
+    FieldSpec::fraction("master sword bosses", 0x426, 4, 0, 13),
+    FieldSpec::fraction("tempered sword bosses", 0x425, 4, 4, 13),
+    FieldSpec::fraction("golden sword bosses", 0x425, 4, 0, 13),
+    FieldSpec::number("locations pre boots", 0x432, 8, 0),
+    FieldSpec::number("locations pre mirror", 0x433, 8, 0),
+    BONKS,
+    FieldSpec::number("overworld mirrors", 0x43A, 8, 0),
+    FieldSpec::number("underworld mirrors", 0x43B, 8, 0),
+    FieldSpec::number("times fluted", 0x44B, 8, 0),
+    FieldSpec::number("screen transitions", 0x43C, 16, 0),
+    FieldSpec::number("rupees spent", 0x42B, 16, 0),
+    SAVE_AND_QUITS,
+    DEATHS,
+    TOTAL_TIME,
+    MENU_TIME,
+    FIRST_SWORD,
+    BOOTS_FOUND,
+    FLUTE_FOUND,
+    MIRROR_FOUND,
+    FAERIE_REVIVALS,
+];
+
+/// The locations-checked stats, one per dungeon.
+pub const DUNGEONS: [&str; 13] = [
+    "hyrule castle",
+    "eastern palace",
+    "desert palace",
+    "tower of hera",
+    "castle tower",
+    "palace of darkness",
+    "swamp palace",
+    "skull woods",
+    "thieves town",
+    "ice palace",
+    "misery mire",
+    "turtle rock",
+    "ganons tower",
+];
+
+/// The bosses-by-sword stats, indexed by sword level.
+pub const BOSS_STATS: [&str; 5] = [
+    "swordless bosses",
+    "fighter sword bosses",
+    "master sword bosses",
+    "tempered sword bosses",
+    "golden sword bosses",
+];
+
+/// The frame counter for time spent outside of lag frames, used to derive
+/// "lag time".
+pub const LOOP_TIME: FieldSpec = FieldSpec::time("loop time", 0x42E);
+
+pub const COLLECTION_RATE: FieldSpec = FieldSpec::fraction("collection rate", 0x423, 8, 0, 216);
+pub const CHEST_LOCATIONS: FieldSpec = FieldSpec::number("chest locations", 0x442, 8, 0);
+pub const BONKS: FieldSpec = FieldSpec::number("bonks", 0x420, 8, 0);
+pub const SAVE_AND_QUITS: FieldSpec = FieldSpec::number("save and quits", 0x42D, 8, 0);
+pub const DEATHS: FieldSpec = FieldSpec::number("deaths", 0x449, 8, 0);
+pub const TOTAL_TIME: FieldSpec = FieldSpec::time("total time", 0x43E);
+pub const MENU_TIME: FieldSpec = FieldSpec::time("menu time", 0x444);
+pub const FIRST_SWORD: FieldSpec = FieldSpec::time("first sword", 0x458);
+pub const BOOTS_FOUND: FieldSpec = FieldSpec::time("boots found", 0x45C);
+pub const FLUTE_FOUND: FieldSpec = FieldSpec::time("flute found", 0x460);
+pub const MIRROR_FOUND: FieldSpec = FieldSpec::time("mirror found", 0x464);
+pub const FAERIE_REVIVALS: FieldSpec = FieldSpec::number("faerie revivals", 0x453, 8, 0);
+
+/// The ROM and file name values `read_stats` always includes.
+pub const META_STATS: &[&str] = &[
+    "rom name",
+    "generator",
+    "build",
+    "seed",
+    "team",
+    "hash id",
+    "filename",
+];
+/// The stats `read_stats` computes from other fields.
+pub const DERIVED_STATS: &[&str] = &["other locations", "lag time"];
+
+/// Whether `read_stats` returns a stat called `name`.
+pub fn is_stat(name: &str) -> bool {
+    META_STATS.contains(&name)
+        || DERIVED_STATS.contains(&name)
+        || find_field(STAT_FIELDS, name).is_some()
+}
+
+pub fn read_stats(sram: &[u8], validate: bool) -> Result<Map<&'static str, Z3RStat>> {
+    read_stats_from(sram, validate, STAT_FIELDS)
+}
+
+/// Like `read_stats` but decodes `fields` instead of the built-in table.
+/// Meta values are always included, derived stats only when the fields they
+/// are derived from are present.
+#[rustfmt::skip]
+pub fn read_stats_from<'a>(sram: &[u8], validate: bool, fields: &'a [FieldSpec]) -> Result<Map<&'a str, Z3RStat>> {
+    if validate {
+        validate_sram(sram)?;
+    }
+    let mut sram_stats: Map<&str, Z3RStat> = Map::new();
+
+    let rom_info = read_rom_info(sram, false).ok();
+    let rom_meta = |f: fn(&RomInfo) -> Option<String>| Z3RStat::Meta(rom_info.as_ref().and_then(f));
+    sram_stats.insert("rom name", rom_meta(|r| Some(r.name.clone())));
+    sram_stats.insert("generator", rom_meta(|r| Some(r.generator.to_string())));
+    sram_stats.insert("build", rom_meta(|r| r.build.clone()));
+    sram_stats.insert("seed", rom_meta(|r| r.seed.map(|s| s.to_string())));
+    sram_stats.insert("team", rom_meta(|r| r.team.map(|t| t.to_string())));
+    sram_stats.insert("hash id", rom_meta(|r| r.hash.clone()));
+    sram_stats.insert("filename", z3rfile_to_unicode(sram)?);
+    sram_stats.extend(read_fields(sram, fields)?);
+    if let (Some(total), Some(chests)) = (sram_stats.get("collection rate"), sram_stats.get("chest locations")) {
+        let other = u32::try_from(total)?.saturating_sub(u32::try_from(chests)?);
+        sram_stats.insert("other locations", Z3RStat::Number(other));
+    }
+    if let Some(total_time) = sram_stats.get("total time") {
+        let lag = u32::try_from(total_time)?.saturating_sub(LOOP_TIME.read(sram)?);
+        sram_stats.insert("lag time", Z3RStat::new_time(lag));
     }
-    let mut cur = Cursor::new(sram);
-    let mut sram_stats: HashMap<&str, Z3RStat> = HashMap::with_capacity(58);  
-    
-    let hash_id: Z3RStat = get_hash_id(&sram);
-    match hash_id {
-        Z3RStat::Meta(Some(id)) => {
-            sram_stats.insert("hash id", Z3RStat::Meta(Some(id.clone())));
-            sram_stats.insert("permalink", Z3RStat::Meta(Some(format!("https://alttpr.com/h/{}", id))));
-        },
-        Z3RStat::Meta(None) => {
-            sram_stats.insert("hash id", Z3RStat::Meta(None));
-            sram_stats.insert("permalink", Z3RStat::Meta(None));
-        },
-        _ => (),
-    };
-    let file_name = match z3rfile_to_unicode(&sram) {
-        Ok(name) => name,
-        Err(_) => return Err(anyhow!("Invalid File Name")),
-    };
-    sram_stats.insert("filename", file_name);
-    let total = get_stat(&mut cur, 0x423, 8, 0, Some(216))?;
-    let chests = get_stat(&mut cur, 0x442, 8, 0, None)?;
-    sram_stats.insert("other locations", Z3RStat::Number(u32::try_from(&total)? - u32::try_from(&chests)?));
-    sram_stats.insert("collection rate", total);
-    sram_stats.insert("chest locations", chests);
-    sram_stats.insert("y items", get_stat(&mut cur, 0x421, 5, 3, Some(27))?);
-    sram_stats.insert("a items", get_stat(&mut cur, 0x421, 3, 0, Some(5))?);
-    sram_stats.insert("swords", get_stat(&mut cur, 0x422, 3, 5, Some(4))?);
-    sram_stats.insert("shields", get_stat(&mut cur, 0x422, 2, 3, Some(3))?);
-    sram_stats.insert("mails", get_stat(&mut cur, 0x424, 2, 6, Some(3))?);
-    sram_stats.insert("capacity upgrades", get_stat(&mut cur, 0x452, 4, 0, Some(15))?);
-    sram_stats.insert("heart containers", get_stat(&mut cur, 0x429, 4, 4, Some(11))?);
-    sram_stats.insert("heart pieces", get_stat(&mut cur, 0x448, 8, 0, Some(24))?);
-    sram_stats.insert("maps", get_stat(&mut cur, 0x428, 4, 4, Some(12))?);
-    sram_stats.insert("compasses", get_stat(&mut cur, 0x428, 4, 0, Some(11))?);
-    sram_stats.insert("small keys", get_stat(&mut cur, 0x424, 6, 0, Some(61))?);
-    sram_stats.insert("big keys", get_stat(&mut cur, 0x427, 4, 4, Some(12))?);
-    sram_stats.insert("big chests", get_stat(&mut cur, 0x427, 4, 0, Some(11))?);
-    sram_stats.insert("pendants", get_stat(&mut cur, 0x429, 2, 0, Some(3))?);
-    sram_stats.insert("crystals", get_stat(&mut cur, 0x422, 3, 0, Some(7))?);
-    sram_stats.insert("hyrule castle", get_stat(&mut cur, 0x434, 4, 4, Some(8))?);
-    sram_stats.insert("eastern palace", get_stat(&mut cur, 0x436, 3, 0, Some(6))?);
-    sram_stats.insert("desert palace", get_stat(&mut cur, 0x435, 3, 5, Some(6))?);
-    sram_stats.insert("tower of hera", get_stat(&mut cur, 0x435, 3, 2, Some(5))?);
-    sram_stats.insert("castle tower", get_stat(&mut cur, 0x435, 2, 0, Some(2))?);
-    sram_stats.insert("palace of darkness", get_stat(&mut cur, 0x434, 4, 0, Some(14))?);
-    sram_stats.insert("swamp palace", get_stat(&mut cur, 0x439, 4, 0, Some(10))?);
-    sram_stats.insert("skull woods", get_stat(&mut cur, 0x437, 4, 4, Some(8))?);
-    sram_stats.insert("thieves town", get_stat(&mut cur, 0x437, 4, 0, Some(8))?);
-    sram_stats.insert("ice palace", get_stat(&mut cur, 0x438, 4, 4, Some(8))?);
-    sram_stats.insert("misery mire", get_stat(&mut cur, 0x438, 4, 0, Some(8))?);
-    sram_stats.insert("turtle rock", get_stat(&mut cur, 0x439, 4, 4, Some(12))?);
-    sram_stats.insert("ganons tower", get_stat(&mut cur, 0x436, 5, 3, Some(27))?);
-    sram_stats.insert("ganons tower big key", get_stat(&mut cur, 0x42A, 5, 0, Some(22))?);
-    sram_stats.insert("swordless bosses", get_stat(&mut cur, 0x452, 4, 4, Some(13))?);
-    sram_stats.insert("fighter sword bosses", get_stat(&mut cur, 0x426, 4, 4, Some(13))?);
-    sram_stats.insert("master sword bosses", get_stat(&mut cur, 0x426, 4, 0, Some(13))?);
-    sram_stats.insert("tempered sword bosses", get_stat(&mut cur, 0x425, 4, 4, Some(13))?);
-    sram_stats.insert("golden sword bosses", get_stat(&mut cur, 0x425, 4, 0, Some(13))?);
-    sram_stats.insert("locations pre boots", get_stat(&mut cur, 0x432, 8, 0, None)?);
-    sram_stats.insert("locations pre mirror", get_stat(&mut cur, 0x433, 8, 0, None)?);
-    sram_stats.insert("bonks", get_stat(&mut cur, 0x420, 8, 0, None)?);
-    sram_stats.insert("overworld mirrors", get_stat(&mut cur, 0x43A, 8, 0, None)?);
-    sram_stats.insert("underworld mirrors", get_stat(&mut cur, 0x43B, 8, 0, None)?);
-    sram_stats.insert("times fluted", get_stat(&mut cur, 0x44B, 8, 0, None)?);
-    sram_stats.insert("screen transitions", get_stat(&mut cur, 0x43C, 16, 0, None)?);
-    sram_stats.insert("rupees spent", get_stat(&mut cur, 0x42B, 16, 0, None)?);
-    sram_stats.insert("save and quits", get_stat(&mut cur, 0x42D, 8, 0, None)?);
-    sram_stats.insert("deaths", get_stat(&mut cur, 0x449, 8, 0, None)?);
-    let total_time = Z3RStat::new_time(Some(&mut cur), 0x43Eu64);
-    let menu_time = Z3RStat::new_time(Some(&mut cur), 0x444u64);
-    let loop_time = Z3RStat::new_time(Some(&mut cur), 0x42Eu64);
-    sram_stats.insert("lag time", Z3RStat::new_time(None, u32::try_from(&total_time)? - u32::try_from(&loop_time)?));
-    sram_stats.insert("total time", total_time);
-    sram_stats.insert("menu time", menu_time);
-    sram_stats.insert("first sword", Z3RStat::new_time(Some(&mut cur), 0x458u64));
-    sram_stats.insert("boots found", Z3RStat::new_time(Some(&mut cur), 0x45Cu64));
-    sram_stats.insert("flute found", Z3RStat::new_time(Some(&mut cur), 0x460u64));
-    sram_stats.insert("mirror found", Z3RStat::new_time(Some(&mut cur), 0x464u64));
-    sram_stats.insert("faerie revivals", get_stat(&mut cur, 0x453, 8, 0, None)?);
 
     Ok(sram_stats)
 }
 
-fn get_stat(
-    cur: &mut Cursor<&[u8]>,
-    offset: u64,
-    bits: u32,
-    shift: u32,
-    max: Option<u8>,
-) -> Result<Z3RStat> {
-    cur.set_position(offset);
-    let bytes: f32 = ((bits as f32 + shift as f32) / 8f32).ceil();
-    let mut value = match bytes as u8 {
-        1 => cur.read_u8().unwrap() as u32,
-        2 => cur.read_u16::<LittleEndian>().unwrap() as u32,
-        _ => return Err(anyhow!("Tried reading more than two bytes at {}", offset)),
-    };
-    value >>= shift;
-    value &= bitmask(bits);
+/// Decodes a single field into a stat.
+pub fn get_stat(sram: &[u8], field: &FieldSpec) -> Result<Z3RStat> {
+    let value = read_field(sram, field)?;
 
-    match max {
-        Some(max) => Ok(Z3RStat::Fraction(format!("{}/{}", value, max))),
-        None => Ok(Z3RStat::Number(value)),
+    match (field.kind, field.max) {
+        (FieldKind::Time, _) => Ok(Z3RStat::new_time(value)),
+        (FieldKind::Fraction, Some(max)) => Ok(Z3RStat::Fraction(format!("{}/{}", value, max))),
+        _ => Ok(Z3RStat::Number(value)),
     }
 }
 
+/// Decodes every field in `fields`, keyed by field name.
+pub fn read_fields<'a>(sram: &[u8], fields: &'a [FieldSpec]) -> Result<Map<&'a str, Z3RStat>> {
+    let mut stats: Map<&str, Z3RStat> = Map::new();
+    for field in fields {
+        stats.insert(field.name.as_ref(), get_stat(sram, field)?);
+    }
+
+    Ok(stats)
+}
+
+#[rustfmt::skip]
+const NAME_ENCODING: [&str; 207] = [
+    "あ", "い", "う", "え", "お", "や", "ゆ", "よ", "か", "き", "く", "け", "こ", "わ", "を",
+    "ん", "さ", "し", "す", "せ", "そ", "が", "ぎ", "ぐ", "た", "ち", "つ", "て", "と", "げ",
+    "ご", "ざ", "な", "に", "ぬ", "ね", "の", "じ", "ず", "ぜ", "は", "ひ", "ふ", "へ", "ほ",
+    "ぞ", "だ", "ぢ", "ま", "み", "む", "め", "も", "づ", "で", "ど", "ら", "り", "る", "れ",
+    "ろ", "ば", "び", "ぶ", "べ", "ぼ", "ぱ", "ぴ", "ぷ", "ぺ", "ぽ", "ゃ", "ゅ", "ょ", "っ",
+    "ぁ", "ぃ", "ぅ", "ぇ", "ぉ", "ア", "イ", "ウ", "エ", "オ", "ヤ", "ユ", "ヨ", "カ", "キ",
+    "ク", "ケ", "コ", "ワ", "ヲ", "ン", "サ", "シ", "ス", "セ", "ソ", "ガ", "ギ", "グ", "タ",
+    "チ", "ツ", "テ", "ト", "ゲ", "ゴ", "ザ", "ナ", "ニ", "ヌ", "ネ", "ノ", "ジ", "ズ", "ゼ",
+    "ハ", "ヒ", "フ", "ヘ", "ホ", "ゾ", "ダ", "ヂ", "マ", "ミ", "ム", "メ", "モ", "ヅ", "デ",
+    "ド", "ラ", "リ", "ル", "レ", "ロ", "バ", "ビ", "ブ", "ベ", "ボ", "パ", "ピ", "プ", "ペ",
+    "ポ", "ャ", "ュ", "ョ", "ッ", "ァ", "ィ", "ゥ", "ェ", "ォ", "0", "1", "2", "3", "4", "5",
+    "6", "7", "8", "9", "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N",
+    "O", "P", "Q", "R", "S", "T", "U", "V", "W", "X", "Y", "Z", "「", "」", "?", "!", ",", "-",
+    "<", ">", " ", "。", "~",
+];
+
 fn z3rfile_to_unicode(sram: &[u8]) -> Result<Z3RStat> {
-    const NAME_ENCODING: [&str; 207] = [
-        "あ", "い", "う", "え", "お", "や", "ゆ", "よ", "か", "き", "く", "け", "こ", "わ", "を",
-        "ん", "さ", "し", "す", "せ", "そ", "が", "ぎ", "ぐ", "た", "ち", "つ", "て", "と", "げ",
-        "ご", "ざ", "な", "に", "ぬ", "ね", "の", "じ", "ず", "ぜ", "は", "ひ", "ふ", "へ", "ほ",
-        "ぞ", "だ", "ぢ", "ま", "み", "む", "め", "も", "づ", "で", "ど", "ら", "り", "る", "れ",
-        "ろ", "ば", "び", "ぶ", "べ", "ぼ", "ぱ", "ぴ", "ぷ", "ぺ", "ぽ", "ゃ", "ゅ", "ょ", "っ",
-        "ぁ", "ぃ", "ぅ", "ぇ", "ぉ", "ア", "イ", "ウ", "エ", "オ", "ヤ", "ユ", "ヨ", "カ", "キ",
-        "ク", "ケ", "コ", "ワ", "ヲ", "ン", "サ", "シ", "ス", "セ", "ソ", "ガ", "ギ", "グ", "タ",
-        "チ", "ツ", "テ", "ト", "ゲ", "ゴ", "ザ", "ナ", "ニ", "ヌ", "ネ", "ノ", "ジ", "ズ", "ゼ",
-        "ハ", "ヒ", "フ", "ヘ", "ホ", "ゾ", "ダ", "ヂ", "マ", "ミ", "ム", "メ", "モ", "ヅ", "デ",
-        "ド", "ラ", "リ", "ル", "レ", "ロ", "バ", "ビ", "ブ", "ベ", "ボ", "パ", "ピ", "プ", "ペ",
-        "ポ", "ャ", "ュ", "ョ", "ッ", "ァ", "ィ", "ゥ", "ェ", "ォ", "0", "1", "2", "3", "4", "5",
-        "6", "7", "8", "9", "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N",
-        "O", "P", "Q", "R", "S", "T", "U", "V", "W", "X", "Y", "Z", "「", "」", "?", "!", ",", "-",
-        "<", ">", " ", "。", "~",
-    ];
     let mut file_name = String::with_capacity(36); // Avoid re-allocation w/ multi byte characters
-    let mut cur = Cursor::new(sram);
-    cur.set_position(0x3d9);
-    for _ in 0..4 {
-        let character = cur.read_u16::<LittleEndian>()?;
-        let char_index = (character & 0xF) | ((character >> 1) & 0xF0);
-        file_name.push_str(NAME_ENCODING[char_index as usize]);
-    }
-    cur.set_position(0x500);
-    for _ in 0..8 {
-        let character = cur.read_u16::<LittleEndian>()?;
+    let tiles = match (sram.get(0x3D9..0x3E1), sram.get(0x500..0x510)) {
+        (Some(first), Some(last)) => first.chunks_exact(2).chain(last.chunks_exact(2)),
+        _ => return Err(Error::InvalidFileName),
+    };
+    for tile in tiles {
+        let character = LittleEndian::read_u16(tile);
         let char_index = (character & 0xF) | ((character >> 1) & 0xF0);
-        file_name.push_str(NAME_ENCODING[char_index as usize]);
+        match NAME_ENCODING.get(char_index as usize) {
+            Some(c) => file_name.push_str(c),
+            None => return Err(Error::InvalidFileName),
+        }
     }
 
     Ok(Z3RStat::Meta(Some(file_name)))
 }
 
-fn get_hash_id(sram: &[u8]) -> Z3RStat {
-    let rom_name = &sram[0x2000..0x2002];
-    match from_utf8(&rom_name).unwrap() {
-        "VT" => {
-            let hash_id = from_utf8(&sram[0x2003..0x200D]).unwrap();
-            return Z3RStat::Meta(Some(hash_id.to_string()));
-        }
-        _ => return Z3RStat::Meta(None),
-    };
+/// Encodes a file name into the 12 tile words the game stores at 0x3D9 (first
+/// four characters) and 0x500 (last eight). Names shorter than 12 characters
+/// are padded with spaces.
+pub fn encode_filename(name: &str) -> Result<[u16; 12]> {
+    if name.chars().count() > 12 {
+        return Err(Error::FileNameTooLong(name.chars().count()));
+    }
+    let mut encoded = [0u16; 12];
+    let mut chars = name.chars().chain(iter::repeat(' '));
+    for (i, tile) in encoded.iter_mut().enumerate() {
+        let c = chars.next().unwrap();
+        let char_index = match NAME_ENCODING.iter().position(|&x| x.starts_with(c)) {
+            Some(idx) => idx as u16,
+            None => {
+                return Err(Error::UnencodableCharacter {
+                    character: c,
+                    position: i,
+                })
+            }
+        };
+        *tile = (char_index & 0xF) | ((char_index & 0xF0) << 1);
+    }
+
+    Ok(encoded)
 }
@@ -0,0 +1,299 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    batch::BatchInput,
+    rom::{read_rom_info, Generator, RomInfo},
+    validate_sram,
+};
+
+/// Why a submission doesn't belong with the rest of the batch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SeedIssue {
+    /// The file couldn't be read, validated or didn't have a ROM name.
+    Unreadable(String),
+    /// The file came from a different randomizer than the seed.
+    GeneratorMismatch {
+        expected: Generator,
+        found: Generator,
+    },
+    /// Same randomizer but a different seed hash.
+    HashMismatch {
+        expected: Option<String>,
+        found: Option<String>,
+    },
+    /// Same randomizer and hash but the ROM names differ, e.g. a different
+    /// build tag or a different ER seed.
+    RomNameMismatch { expected: String, found: String },
+    /// Byte for byte the same file as an earlier submission.
+    Duplicate { of: String },
+}
+
+/// One problem with one submission.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubmissionFinding {
+    pub source: String,
+    pub issue: SeedIssue,
+}
+
+impl fmt::Display for SubmissionFinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.issue {
+            SeedIssue::Unreadable(e) => write!(f, "{}: {}", self.source, e),
+            SeedIssue::GeneratorMismatch { expected, found } => write!(
+                f,
+                "{}: generated by {}, expected {}",
+                self.source, found, expected
+            ),
+            SeedIssue::HashMismatch { expected, found } => write!(
+                f,
+                "{}: hash id {}, expected {}",
+                self.source,
+                found.as_deref().unwrap_or("none"),
+                expected.as_deref().unwrap_or("none")
+            ),
+            SeedIssue::RomNameMismatch { expected, found } => write!(
+                f,
+                "{}: ROM name \"{}\", expected \"{}\"",
+                self.source, found, expected
+            ),
+            SeedIssue::Duplicate { of } => write!(f, "{}: duplicate of {}", self.source, of),
+        }
+    }
+}
+
+/// The result of checking a batch of submissions for one match.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SeedReport {
+    /// The seed most submissions were played on, or `None` if no file could
+    /// be read.
+    pub seed: Option<RomInfo>,
+    pub findings: Vec<SubmissionFinding>,
+}
+
+impl SeedReport {
+    pub fn is_consistent(&self) -> bool {
+        self.seed.is_some() && self.findings.is_empty()
+    }
+}
+
+/// Checks that every submission was played on the same seed. The seed is
+/// the ROM name most submissions share, with ties going to the earliest
+/// submission, and every other file is reported as an outlier. Identical
+/// files are reported against the first submission with that content.
+pub fn check_submissions(inputs: &[BatchInput], validate: bool) -> SeedReport {
+    let mut findings: Vec<SubmissionFinding> = Vec::new();
+    let mut seen: HashMap<u64, String> = HashMap::new();
+    let mut roms: Vec<(String, RomInfo)> = Vec::with_capacity(inputs.len());
+
+    for input in inputs {
+        let source = input.name();
+        let read = input.load().and_then(|sram| {
+            if validate {
+                validate_sram(&sram)?;
+            }
+            Ok((fingerprint(&sram), read_rom_info(&sram, false)?))
+        });
+        let (print, rom) = match read {
+            Ok(read) => read,
+            Err(e) => {
+                let issue = SeedIssue::Unreadable(e.to_string());
+                findings.push(SubmissionFinding { source, issue });
+                continue;
+            }
+        };
+        if let Some(of) = seen.get(&print) {
+            let issue = SeedIssue::Duplicate { of: of.clone() };
+            findings.push(SubmissionFinding {
+                source: source.clone(),
+                issue,
+            });
+        } else {
+            seen.insert(print, source.clone());
+        }
+        roms.push((source, rom));
+    }
+
+    let seed = majority(&roms);
+    if let Some(seed) = &seed {
+        for (source, rom) in roms.into_iter() {
+            if let Some(issue) = compare_seed(seed, rom) {
+                findings.push(SubmissionFinding { source, issue });
+            }
+        }
+    }
+
+    SeedReport { seed, findings }
+}
+
+/// A 64 bit FNV-1a hash of the whole file, used to spot resubmitted files.
+/// Unlike `std`'s hasher it's stable between builds so it can be stored.
+pub fn fingerprint(sram: &[u8]) -> u64 {
+    sram.iter().fold(0xCBF2_9CE4_8422_2325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x0100_0000_01B3)
+    })
+}
+
+fn majority(roms: &[(String, RomInfo)]) -> Option<RomInfo> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for (_, rom) in roms {
+        *counts.entry(rom.name.as_str()).or_insert(0) += 1;
+    }
+    let most = counts.values().copied().max()?;
+
+    roms.iter()
+        .map(|(_, rom)| rom)
+        .find(|rom| counts[rom.name.as_str()] == most)
+        .cloned()
+}
+
+fn compare_seed(seed: &RomInfo, rom: RomInfo) -> Option<SeedIssue> {
+    if rom.generator != seed.generator {
+        Some(SeedIssue::GeneratorMismatch {
+            expected: seed.generator,
+            found: rom.generator,
+        })
+    } else if rom.hash != seed.hash {
+        Some(SeedIssue::HashMismatch {
+            expected: seed.hash.clone(),
+            found: rom.hash,
+        })
+    } else if rom.name != seed.name {
+        Some(SeedIssue::RomNameMismatch {
+            expected: seed.name.clone(),
+            found: rom.name,
+        })
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{fix_checksum, titled};
+
+    // Files differ in their death count so only `same` files are identical
+    fn file(title: &str, deaths: u8) -> Vec<u8> {
+        let mut sram = titled(title);
+        sram[0x449] = deaths;
+        fix_checksum(&mut sram);
+        sram
+    }
+
+    fn check(files: &[(&str, &[u8])]) -> SeedReport {
+        let inputs: Vec<BatchInput> = files
+            .iter()
+            .map(|(name, sram)| BatchInput::Buffer {
+                name: name.to_string(),
+                sram,
+            })
+            .collect();
+        check_submissions(&inputs, true)
+    }
+
+    fn issues(report: &SeedReport) -> Vec<(&str, &SeedIssue)> {
+        report
+            .findings
+            .iter()
+            .map(|f| (f.source.as_str(), &f.issue))
+            .collect()
+    }
+
+    const SEED: &str = "VT 1234567890";
+    const OTHER: &str = "VT ABCDEFGHIJ";
+
+    #[test]
+    fn majority_picks_the_seed() {
+        let (a, b, c) = (file(OTHER, 0), file(SEED, 1), file(SEED, 2));
+        let report = check(&[("a", &a), ("b", &b), ("c", &c)]);
+
+        assert_eq!(report.seed.as_ref().map(|r| r.name.as_str()), Some(SEED));
+        assert_eq!(
+            issues(&report),
+            [(
+                "a",
+                &SeedIssue::HashMismatch {
+                    expected: Some("1234567890".to_string()),
+                    found: Some("ABCDEFGHIJ".to_string()),
+                }
+            )]
+        );
+        assert!(!report.is_consistent());
+    }
+
+    #[test]
+    fn ties_go_to_the_earliest_submission() {
+        let (a, b) = (file(OTHER, 0), file(SEED, 1));
+        let report = check(&[("a", &a), ("b", &b)]);
+
+        assert_eq!(report.seed.map(|r| r.name), Some(OTHER.to_string()));
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].source, "b");
+    }
+
+    #[test]
+    fn duplicates_are_reported_against_the_first() {
+        let (a, b) = (file(SEED, 0), file(SEED, 1));
+        let report = check(&[("a", &a), ("b", &b), ("c", &a)]);
+
+        assert_eq!(
+            issues(&report),
+            [(
+                "c",
+                &SeedIssue::Duplicate {
+                    of: "a".to_string()
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn generator_is_compared_before_hash_and_name() {
+        let (seed, er, build) = (
+            file(SEED, 0),
+            file("ER400_1_123456789", 1),
+            file("VT 1234567890 v31", 2),
+        );
+        let report = check(&[("a", &seed), ("b", &seed), ("er", &er), ("build", &build)]);
+        let issues = issues(&report);
+
+        assert!(matches!(issues[0], ("b", SeedIssue::Duplicate { .. })));
+        assert_eq!(
+            issues[1],
+            (
+                "er",
+                &SeedIssue::GeneratorMismatch {
+                    expected: Generator::Alttpr,
+                    found: Generator::EntranceRandomizer,
+                }
+            )
+        );
+        assert_eq!(
+            issues[2],
+            (
+                "build",
+                &SeedIssue::RomNameMismatch {
+                    expected: SEED.to_string(),
+                    found: "VT 1234567890 v31".to_string(),
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn unreadable_files_are_reported_and_not_counted() {
+        let seed = file(SEED, 0);
+        let report = check(&[("short", &[0; 16]), ("a", &seed)]);
+        assert_eq!(report.seed.as_ref().map(|r| r.name.as_str()), Some(SEED));
+        assert!(matches!(
+            issues(&report)[..],
+            [("short", SeedIssue::Unreadable(_))]
+        ));
+
+        let missing = [BatchInput::Path("does/not/exist.srm".into())];
+        let report = check_submissions(&missing, false);
+        assert_eq!(report.seed, None);
+        assert!(!report.is_consistent());
+        assert_eq!(report.findings.len(), 1);
+    }
+}
@@ -0,0 +1,47 @@
+// Builders for the files the unit tests parse.
+
+/// A freshly created VT file: the validity word and file marker are set, the
+/// file name is all spaces and the checksum is correct.
+pub(crate) fn blank() -> Vec<u8> {
+    let mut sram = vec![0u8; 32768];
+    sram[0x3E1..0x3E3].copy_from_slice(&0x55AAu16.to_le_bytes());
+    sram[0x4F0] = 0xFF;
+    sram[0x2000..0x2015].copy_from_slice(b"VT 1234567890        ");
+    // The space tile is 0x18C
+    for offset in (0x3D9..0x3E1).chain(0x500..0x510).step_by(2) {
+        sram[offset..offset + 2].copy_from_slice(&0x18Cu16.to_le_bytes());
+    }
+    fix_checksum(&mut sram);
+
+    sram
+}
+
+/// A blank file with single bytes changed, given as `(offset, value)` pairs,
+/// and the checksum fixed afterwards.
+#[cfg(feature = "alloc")]
+pub(crate) fn edited(edits: &[(usize, u8)]) -> Vec<u8> {
+    let mut sram = blank();
+    for (offset, value) in edits {
+        sram[*offset] = *value;
+    }
+    fix_checksum(&mut sram);
+
+    sram
+}
+
+/// A blank file with a different ROM title, padded with spaces.
+#[cfg(feature = "alloc")]
+pub(crate) fn titled(title: &str) -> Vec<u8> {
+    let mut sram = blank();
+    sram[0x2000..0x2015].fill(b' ');
+    sram[0x2000..0x2000 + title.len()].copy_from_slice(title.as_bytes());
+    fix_checksum(&mut sram);
+
+    sram
+}
+
+/// Rewrites the inverse checksum after a test edits the file.
+pub(crate) fn fix_checksum(sram: &mut [u8]) {
+    let checksum = crate::inverse_checksum(sram).expect("sram is a full file");
+    sram[0x4FE..0x500].copy_from_slice(&checksum.to_le_bytes());
+}
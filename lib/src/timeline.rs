@@ -0,0 +1,290 @@
+use core::{convert::TryFrom, fmt};
+
+use crate::{
+    equipment::Z3REquip,
+    prelude::*,
+    stats::{BOSS_STATS, DUNGEONS},
+    ParsedSram,
+};
+
+/// Equipment that counts as an item when it's gained or upgraded. Current
+/// ammo, health and the derived capacities are left out since they change
+/// without an item being collected.
+const ITEMS: [&str; 33] = [
+    "bow",
+    "silver arrows",
+    "blue boomerang",
+    "red boomerang",
+    "hookshot",
+    "mushroom",
+    "powder",
+    "fire rod",
+    "ice rod",
+    "bombos",
+    "ether",
+    "quake",
+    "lamp",
+    "hammer",
+    "shovel",
+    "flute",
+    "bug net",
+    "book",
+    "somaria",
+    "byrna",
+    "cape",
+    "mirror",
+    "gloves",
+    "boots",
+    "flippers",
+    "moon pearl",
+    "sword",
+    "shield",
+    "mail",
+    "magic consumption",
+    "bomb upgrades",
+    "arrow upgrades",
+    "goal items",
+];
+
+// Bottle contents change as they're filled and emptied, so only getting the
+// bottle itself is an event
+const BOTTLES: [&str; 4] = ["bottle 1", "bottle 2", "bottle 3", "bottle 4"];
+
+const PENDANTS: [&str; 3] = ["green pendant", "blue pendant", "red pendant"];
+
+const CRYSTALS: [&str; 7] = [
+    "crystal 1",
+    "crystal 2",
+    "crystal 3",
+    "crystal 4",
+    "crystal 5",
+    "crystal 6",
+    "crystal 7",
+];
+
+/// Something that happened between two snapshots.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum EventKind {
+    /// An item was collected or upgraded. `value` is the new equipment
+    /// value, e.g. 2 for the master sword.
+    ItemAcquired {
+        item: &'static str,
+        value: u32,
+    },
+    PendantObtained(&'static str),
+    /// Crystals are numbered 1 to 7.
+    CrystalObtained(u32),
+    Death,
+    SaveAndQuit,
+    DungeonLocationChecked(&'static str),
+    /// `sword` is the sword level the boss was defeated with, 0 for none.
+    BossDefeated {
+        sword: u32,
+    },
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ItemAcquired { item, .. } => write!(f, "{}", item),
+            Self::PendantObtained(pendant) => write!(f, "{}", pendant),
+            Self::CrystalObtained(n) => write!(f, "crystal {}", n),
+            Self::Death => write!(f, "death"),
+            Self::SaveAndQuit => write!(f, "save and quit"),
+            Self::DungeonLocationChecked(dungeon) => write!(f, "{} location", dungeon),
+            Self::BossDefeated { .. } => write!(f, "boss defeated"),
+        }
+    }
+}
+
+/// An event and the "total time" of the snapshot it was first seen in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TimelineEvent {
+    pub frame: u32,
+    pub kind: EventKind,
+}
+
+/// A run log built from successive snapshots of the same file. Counters
+/// that went up by more than one between snapshots produce one event per
+/// step, all with the same timestamp.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Timeline {
+    last: Option<ParsedSram>,
+    events: Vec<TimelineEvent>,
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Timeline {
+            last: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// Records the events since the previous snapshot and returns them. The
+    /// first snapshot only sets the starting point and produces no events.
+    pub fn push(&mut self, snapshot: ParsedSram) -> &[TimelineEvent] {
+        let start = self.events.len();
+        if let Some(last) = &self.last {
+            self.events.extend(diff_snapshots(last, &snapshot));
+        }
+        self.last = Some(snapshot);
+
+        &self.events[start..]
+    }
+
+    pub fn events(&self) -> &[TimelineEvent] {
+        &self.events
+    }
+
+    pub fn last_snapshot(&self) -> Option<&ParsedSram> {
+        self.last.as_ref()
+    }
+}
+
+/// Every event between `before` and `after`, timestamped with the total
+/// time in `after`.
+pub fn diff_snapshots(before: &ParsedSram, after: &ParsedSram) -> Vec<TimelineEvent> {
+    let frame = stat(after, "total time");
+    let mut kinds: Vec<EventKind> = Vec::new();
+
+    for item in ITEMS.iter() {
+        let value = equipment(after, item);
+        if value > equipment(before, item) {
+            kinds.push(EventKind::ItemAcquired { item, value });
+        }
+    }
+    for bottle in BOTTLES.iter() {
+        let value = equipment(after, bottle);
+        if equipment(before, bottle) == 0 && value > 0 {
+            kinds.push(EventKind::ItemAcquired {
+                item: bottle,
+                value,
+            });
+        }
+    }
+    for pendant in PENDANTS.iter() {
+        if equipment(after, pendant) > equipment(before, pendant) {
+            kinds.push(EventKind::PendantObtained(pendant));
+        }
+    }
+    for (n, crystal) in (1..).zip(CRYSTALS.iter()) {
+        if equipment(after, crystal) > equipment(before, crystal) {
+            kinds.push(EventKind::CrystalObtained(n));
+        }
+    }
+
+    let mut repeat = |kind: EventKind, name: &str| {
+        let count = stat(after, name).saturating_sub(stat(before, name));
+        kinds.extend((0..count).map(|_| kind));
+    };
+    repeat(EventKind::Death, "deaths");
+    repeat(EventKind::SaveAndQuit, "save and quits");
+    for dungeon in DUNGEONS.iter() {
+        repeat(EventKind::DungeonLocationChecked(dungeon), dungeon);
+    }
+    for (sword, name) in (0..).zip(BOSS_STATS.iter()) {
+        repeat(EventKind::BossDefeated { sword }, name);
+    }
+
+    kinds
+        .into_iter()
+        .map(|kind| TimelineEvent { frame, kind })
+        .collect()
+}
+
+fn stat(parsed: &ParsedSram, name: &str) -> u32 {
+    parsed
+        .stats
+        .get(name)
+        .and_then(|s| u32::try_from(s).ok())
+        .unwrap_or(0)
+}
+
+fn equipment(parsed: &ParsedSram, name: &str) -> u32 {
+    parsed.equipment.get(name).map_or(0, Z3REquip::value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_sram, test_util::edited};
+
+    // Total time is set so events carry a recognizable frame
+    fn snapshot(edits: &[(usize, u8)]) -> ParsedSram {
+        let mut edits = edits.to_vec();
+        edits.push((0x43E, 100));
+        parse_sram(&edited(&edits), false).unwrap()
+    }
+
+    fn kinds(before: &[(usize, u8)], after: &[(usize, u8)]) -> Vec<EventKind> {
+        diff_snapshots(&snapshot(before), &snapshot(after))
+            .into_iter()
+            .map(|e| e.kind)
+            .collect()
+    }
+
+    #[test]
+    fn items_are_stamped_with_the_later_total_time() {
+        let events = diff_snapshots(&snapshot(&[]), &snapshot(&[(0x359, 2)]));
+        assert_eq!(
+            events,
+            [TimelineEvent {
+                frame: 100,
+                kind: EventKind::ItemAcquired {
+                    item: "sword",
+                    value: 2
+                },
+            }]
+        );
+        assert!(kinds(&[(0x359, 2)], &[(0x359, 2)]).is_empty());
+    }
+
+    #[test]
+    fn counters_repeat_once_per_step() {
+        // Three deaths and two eastern palace locations between snapshots
+        assert_eq!(
+            kinds(&[(0x449, 1)], &[(0x449, 4), (0x436, 2)]),
+            [
+                EventKind::Death,
+                EventKind::Death,
+                EventKind::Death,
+                EventKind::DungeonLocationChecked("eastern palace"),
+                EventKind::DungeonLocationChecked("eastern palace"),
+            ]
+        );
+    }
+
+    #[test]
+    fn bottles_count_only_when_first_obtained() {
+        assert_eq!(
+            kinds(&[], &[(0x35C, 2)]),
+            [EventKind::ItemAcquired {
+                item: "bottle 1",
+                value: 2
+            }]
+        );
+        // Filling a bottle isn't an event
+        assert!(kinds(&[(0x35C, 2)], &[(0x35C, 6)]).is_empty());
+    }
+
+    #[test]
+    fn crystals_are_numbered_by_crystal() {
+        // Crystal 6 is bit 0 and crystal 1 is bit 1 of 0x37A
+        assert_eq!(
+            kinds(&[], &[(0x37A, 0x03)]),
+            [EventKind::CrystalObtained(1), EventKind::CrystalObtained(6)]
+        );
+    }
+
+    #[test]
+    fn first_snapshot_sets_the_starting_point() {
+        let mut timeline = Timeline::new();
+        assert!(timeline.push(snapshot(&[(0x449, 2)])).is_empty());
+        assert_eq!(timeline.push(snapshot(&[(0x449, 3)])).len(), 1);
+        assert_eq!(timeline.events().len(), 1);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{
+    equipment::{get_equipment, Z3REquip, EQUIPMENT_FIELDS},
+    error::{Error, Result},
+    field::{find_field, read_field, FieldSpec},
+    prelude::*,
+    rom::{read_rom_info, RomInfo},
+    stats::{
+        get_stat, Z3RStat, BONKS, BOOTS_FOUND, CHEST_LOCATIONS, COLLECTION_RATE, DEATHS,
+        FAERIE_REVIVALS, FIRST_SWORD, FLUTE_FOUND, LOOP_TIME, MENU_TIME, MIRROR_FOUND,
+        SAVE_AND_QUITS, STAT_FIELDS, TOTAL_TIME,
+    },
+    validate_sram,
+};
+
+/// A borrowed SRAM that decodes single fields on demand instead of building
+/// the full stat and equipment maps. Times are returned as raw frame counts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SramView<'a> {
+    sram: &'a [u8],
+}
+
+impl<'a> SramView<'a> {
+    /// Wraps `sram` after checking only its size, which is enough for every
+    /// built-in field to be in bounds.
+    pub fn new(sram: &'a [u8]) -> Result<Self> {
+        if sram.len() != 32768 {
+            return Err(Error::InvalidSize);
+        }
+
+        Ok(SramView { sram })
+    }
+
+    /// Wraps `sram` after running the full `validate_sram` checks.
+    pub fn validated(sram: &'a [u8]) -> Result<Self> {
+        validate_sram(sram)?;
+
+        Ok(SramView { sram })
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.sram
+    }
+
+    pub fn field(&self, field: &FieldSpec) -> Result<u32> {
+        read_field(self.sram, field)
+    }
+
+    /// Decodes one stat by its `read_stats` name.
+    pub fn stat(&self, name: &str) -> Result<Z3RStat> {
+        match name {
+            "lag time" => Ok(Z3RStat::new_time(self.lag_time())),
+            "other locations" => Ok(Z3RStat::Number(self.other_locations())),
+            _ => match find_field(STAT_FIELDS, name) {
+                Some(field) => get_stat(self.sram, field),
+                None => Err(Error::UnknownStat(name.to_string())),
+            },
+        }
+    }
+
+    /// Decodes one equipment value by its `read_equipment` name. Derived
+    /// values such as the swap inventory aren't available here.
+    pub fn equipment(&self, name: &str) -> Result<Z3REquip> {
+        match find_field(EQUIPMENT_FIELDS, name) {
+            Some(field) => get_equipment(self.sram, field),
+            None => Err(Error::UnknownEquipment(name.to_string())),
+        }
+    }
+
+    pub fn rom_info(&self) -> Result<RomInfo> {
+        read_rom_info(self.sram, false)
+    }
+
+    pub fn total_time(&self) -> u32 {
+        self.builtin(&TOTAL_TIME)
+    }
+
+    pub fn menu_time(&self) -> u32 {
+        self.builtin(&MENU_TIME)
+    }
+
+    pub fn loop_time(&self) -> u32 {
+        self.builtin(&LOOP_TIME)
+    }
+
+    pub fn lag_time(&self) -> u32 {
+        self.total_time().saturating_sub(self.loop_time())
+    }
+
+    pub fn first_sword(&self) -> u32 {
+        self.builtin(&FIRST_SWORD)
+    }
+
+    pub fn boots_found(&self) -> u32 {
+        self.builtin(&BOOTS_FOUND)
+    }
+
+    pub fn flute_found(&self) -> u32 {
+        self.builtin(&FLUTE_FOUND)
+    }
+
+    pub fn mirror_found(&self) -> u32 {
+        self.builtin(&MIRROR_FOUND)
+    }
+
+    pub fn collection_rate(&self) -> u32 {
+        self.builtin(&COLLECTION_RATE)
+    }
+
+    pub fn chest_locations(&self) -> u32 {
+        self.builtin(&CHEST_LOCATIONS)
+    }
+
+    pub fn other_locations(&self) -> u32 {
+        self.collection_rate()
+            .saturating_sub(self.chest_locations())
+    }
+
+    pub fn deaths(&self) -> u32 {
+        self.builtin(&DEATHS)
+    }
+
+    pub fn faerie_revivals(&self) -> u32 {
+        self.builtin(&FAERIE_REVIVALS)
+    }
+
+    pub fn save_and_quits(&self) -> u32 {
+        self.builtin(&SAVE_AND_QUITS)
+    }
+
+    pub fn bonks(&self) -> u32 {
+        self.builtin(&BONKS)
+    }
+
+    fn builtin(&self, field: &FieldSpec) -> u32 {
+        read_field(self.sram, field).expect("built-in fields are within a 32768 byte SRAM")
+    }
+}
@@ -0,0 +1,207 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{
+    error::{Error, Result},
+    parse_sram,
+    timeline::{diff_snapshots, TimelineEvent},
+    ParsedSram,
+};
+
+/// A file that changed and settled. `events` are the changes since the
+/// previous successful parse of the same file and are empty for the first
+/// one or when parsing failed.
+#[derive(Debug)]
+pub struct WatchUpdate {
+    pub path: PathBuf,
+    pub result: Result<ParsedSram>,
+    pub events: Vec<TimelineEvent>,
+}
+
+#[derive(Debug)]
+struct WatchedFile {
+    modified: Option<SystemTime>,
+    len: u64,
+    changed_at: Option<Instant>,
+    last: Option<ParsedSram>,
+}
+
+/// Polls a directory for SRAM files that emulators have rewritten.
+///
+/// Emulators often write a save in several steps, so a file is only parsed
+/// once its size and modification time have stayed the same for the debounce
+/// period. Files already in the directory are treated as just changed, so
+/// they're reported by the first poll after the debounce period.
+#[derive(Debug)]
+pub struct Watcher {
+    dir: PathBuf,
+    extension: String,
+    interval: Duration,
+    debounce: Duration,
+    validate: bool,
+    files: HashMap<PathBuf, WatchedFile>,
+}
+
+impl Watcher {
+    /// Watches `dir` for `.srm` files, polling every 250ms with a 500ms
+    /// debounce and validating every file.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Watcher {
+            dir: dir.into(),
+            extension: String::from("srm"),
+            interval: Duration::from_millis(250),
+            debounce: Duration::from_millis(500),
+            validate: true,
+            files: HashMap::new(),
+        }
+    }
+
+    pub fn with_extension(mut self, extension: &str) -> Self {
+        self.extension = extension.trim_start_matches('.').to_string();
+        self
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    pub fn with_validation(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Scans the directory once and returns an update for every file that
+    /// has settled since it last changed.
+    pub fn poll(&mut self) -> Result<Vec<WatchUpdate>> {
+        let now = Instant::now();
+        let mut seen: Vec<PathBuf> = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(self.extension.as_str()) {
+                continue;
+            }
+            // The file may have been removed since the directory was read
+            let metadata = match fs::metadata(&path) {
+                Ok(m) if m.is_file() => m,
+                _ => continue,
+            };
+            let (modified, len) = (metadata.modified().ok(), metadata.len());
+            let file = self.files.entry(path.clone()).or_insert(WatchedFile {
+                modified,
+                len,
+                changed_at: Some(now),
+                last: None,
+            });
+            if file.modified != modified || file.len != len {
+                file.modified = modified;
+                file.len = len;
+                file.changed_at = Some(now);
+            }
+            seen.push(path);
+        }
+        self.files.retain(|path, _| seen.contains(path));
+
+        let validate = self.validate;
+        let mut updates: Vec<WatchUpdate> = Vec::new();
+        for (path, file) in self.files.iter_mut() {
+            match file.changed_at {
+                Some(changed) if now.duration_since(changed) >= self.debounce => {
+                    file.changed_at = None;
+                }
+                _ => continue,
+            }
+            let result = fs::read(path)
+                .map_err(Error::from)
+                .and_then(|sram| parse_sram(&sram, validate));
+            let events = match (&file.last, &result) {
+                (Some(last), Ok(parsed)) => diff_snapshots(last, parsed),
+                _ => Vec::new(),
+            };
+            if let Ok(parsed) = &result {
+                file.last = Some(parsed.clone());
+            }
+            updates.push(WatchUpdate {
+                path: path.clone(),
+                result,
+                events,
+            });
+        }
+        updates.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(updates)
+    }
+
+    /// Polls until `callback` returns false or the directory can't be read.
+    pub fn run<F>(mut self, mut callback: F) -> Result<()>
+    where
+        F: FnMut(WatchUpdate) -> bool,
+    {
+        loop {
+            for update in self.poll()? {
+                if !callback(update) {
+                    return Ok(());
+                }
+            }
+            thread::sleep(self.interval);
+        }
+    }
+
+    /// Polls on a new thread and sends updates down a channel. The thread
+    /// returns the error if the directory can't be read.
+    ///
+    /// Dropping the receiver doesn't stop the thread straight away: it only
+    /// notices the next time it has an update to send, so it keeps polling
+    /// until a file changes. Use `run` with your own stop condition if the
+    /// thread has to end promptly.
+    pub fn spawn(self) -> (Receiver<WatchUpdate>, JoinHandle<Result<()>>) {
+        let (tx, rx) = mpsc::channel();
+        let handle = thread::spawn(move || self.run(|update| tx.send(update).is_ok()));
+
+        (rx, handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_existing_files_after_the_debounce() {
+        let dir = std::env::temp_dir().join(format!("z3r-sramr-watch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("run.srm"), vec![0u8; 32768]).unwrap();
+        fs::write(dir.join("notes.txt"), "not a save").unwrap();
+
+        let debounce = Duration::from_millis(50);
+        let mut watcher = Watcher::new(&dir)
+            .with_debounce(debounce)
+            .with_validation(false);
+        let first = watcher.poll().unwrap();
+        thread::sleep(debounce * 2);
+        let second = watcher.poll().unwrap();
+        let third = watcher.poll().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(first.is_empty());
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].path, dir.join("run.srm"));
+        assert!(second[0].result.is_ok());
+        assert!(third.is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::{
+    error::{Error, Result},
+    inverse_checksum,
+    prelude::*,
+    stats::encode_filename,
+};
+
+/// Edits a copy of an SRAM file and keeps its inverse checksum valid.
+pub struct SramWriter {
+    sram: Vec<u8>,
+}
+
+impl SramWriter {
+    pub fn new(sram: &[u8]) -> Result<Self> {
+        if sram.len() != 32768 {
+            return Err(Error::InvalidSize);
+        }
+
+        Ok(SramWriter {
+            sram: sram.to_vec(),
+        })
+    }
+
+    pub fn set_filename(&mut self, name: &str) -> Result<&mut Self> {
+        let encoded = encode_filename(name)?;
+        LittleEndian::write_u16_into(&encoded[..4], &mut self.sram[0x3D9..0x3E1]);
+        LittleEndian::write_u16_into(&encoded[4..], &mut self.sram[0x500..0x510]);
+
+        Ok(self)
+    }
+
+    /// Returns the edited SRAM with a freshly computed inverse checksum.
+    pub fn finish(mut self) -> Result<Vec<u8>> {
+        let checksum = inverse_checksum(&self.sram)?;
+        LittleEndian::write_u16(&mut self.sram[0x4FE..0x500], checksum);
+
+        Ok(self.sram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        stats::{read_stats, Z3RStat},
+        test_util::blank,
+        validate_sram,
+    };
+
+    fn filename(sram: &[u8]) -> Z3RStat {
+        read_stats(sram, true).unwrap().remove("filename").unwrap()
+    }
+
+    #[test]
+    fn file_names_round_trip() {
+        for name in ["LINK", "ゼルダ-123!", "AAAAAAAAAAAA"] {
+            let mut writer = SramWriter::new(&blank()).unwrap();
+            writer.set_filename(name).unwrap();
+            let sram = writer.finish().unwrap();
+            assert!(validate_sram(&sram).is_ok());
+            let padded = format!("{:<12}", name);
+            assert_eq!(filename(&sram), Z3RStat::Meta(Some(padded)));
+        }
+    }
+
+    #[test]
+    fn rejects_names_the_game_cant_show() {
+        let mut writer = SramWriter::new(&blank()).unwrap();
+        assert!(matches!(
+            writer.set_filename("ABCDEFGHIJKLM"),
+            Err(Error::FileNameTooLong(13))
+        ));
+        assert!(matches!(
+            writer.set_filename("link"),
+            Err(Error::UnencodableCharacter {
+                character: 'l',
+                position: 0
+            })
+        ));
+        assert!(matches!(SramWriter::new(&[0; 16]), Err(Error::InvalidSize)));
+    }
+}
@@ -1,183 +1,510 @@
-use std::collections::HashMap;
-
 use pyo3::prelude::*;
-use pyo3::{create_exception, types::PyDict, wrap_pyfunction};
+use pyo3::{
+    create_exception,
+    exceptions::{PyAttributeError, PyException, PyValueError},
+    types::{PyBool, PyBytes, PyDict, PyString},
+};
 
-use z3r_sramr;
 use z3r_sramr::{
-    equipment,
-    equipment::{read_equipment, Z3REquip},
-    stats::{read_stats, Z3RStat},
+    equipment::{display_value, read_equipment, DisplayValue, Z3REquip},
+    error::Error,
+    rom::{read_rom_info, Permalinks},
+    stats::{read_stats, Z3RStat, DUNGEONS},
+    Map,
 };
 
 #[pymodule]
-fn z3rsramr(py: Python, m: &PyModule) -> PyResult<()> {
-    m.add("ParseException", py.get_type::<ParseException>())?;
-    m.add_wrapped(wrap_pyfunction!(validate_sram)).unwrap();
-    m.add_wrapped(wrap_pyfunction!(parse_sram)).unwrap();
+fn z3rsramr(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("ParseException", m.py().get_type::<ParseException>())?;
+    m.add("InvalidSizeError", m.py().get_type::<InvalidSizeError>())?;
+    m.add("InvalidFileError", m.py().get_type::<InvalidFileError>())?;
+    m.add("RomNameError", m.py().get_type::<RomNameError>())?;
+    m.add("ChecksumError", m.py().get_type::<ChecksumError>())?;
+    m.add_class::<Sram>()?;
+    m.add_class::<Stats>()?;
+    m.add_class::<Equipment>()?;
+    m.add_class::<Dungeon>()?;
+    m.add_function(wrap_pyfunction!(validate_sram, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_sram, m)?)?;
 
     Ok(())
 }
 
-#[pyfunction(attr_name = "parse_sram", validate = true)]
-fn parse_sram<'a>(py: Python<'a>, sram: &'a [u8], validate: bool) -> PyResult<&'a PyDict> {
-    if validate {
-        match z3r_sramr::validate_sram(&sram) {
-            Ok(_) => (),
-            Err(e) => return Err(ParseException::py_err(format!("{}", e))),
+/// Permalink templates keyed by ROM title prefix, "VT" or "ER". A `None`
+/// template means that generator gets no permalink.
+type Templates = Map<String, Option<String>>;
+
+#[pyfunction]
+#[pyo3(signature = (sram, validate = true, permalinks = None))]
+fn parse_sram<'py>(
+    py: Python<'py>,
+    sram: &[u8],
+    validate: bool,
+    permalinks: Option<Templates>,
+) -> PyResult<Bound<'py, PyDict>> {
+    Sram::parse(sram, validate, permalinks)?.to_dict(py)
+}
+
+#[pyfunction]
+fn validate_sram(sram: &[u8]) -> bool {
+    z3r_sramr::validate_sram(sram).is_ok()
+}
+
+create_exception!(z3rsramr, ParseException, PyException);
+create_exception!(z3rsramr, InvalidSizeError, ParseException);
+create_exception!(z3rsramr, InvalidFileError, ParseException);
+create_exception!(z3rsramr, RomNameError, ParseException);
+create_exception!(z3rsramr, ChecksumError, ParseException);
+
+fn parse_error(e: Error) -> PyErr {
+    let message = e.to_string();
+    match e {
+        Error::InvalidSize => InvalidSizeError::new_err(message),
+        Error::InvalidFile => InvalidFileError::new_err(message),
+        Error::InvalidRomName => RomNameError::new_err(message),
+        Error::InvalidChecksum => ChecksumError::new_err(message),
+        _ => ParseException::new_err(message),
+    }
+}
+
+fn permalinks(templates: Option<&Templates>) -> PyResult<Permalinks> {
+    templates
+        .into_iter()
+        .flatten()
+        .try_fold(Permalinks::default(), |links, (prefix, template)| {
+            links.with_prefix(prefix, template.as_deref())
+        })
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// A parsed file. `Sram(sram, validate=True)` parses `sram` the same way
+/// `parse_sram` does, and `to_dict()` returns the same dict.
+#[pyclass(module = "z3rsramr", frozen, eq)]
+#[derive(Clone)]
+struct Sram {
+    raw: Vec<u8>,
+    templates: Option<Templates>,
+    #[pyo3(get)]
+    filename: Option<String>,
+    #[pyo3(get)]
+    rom_name: Option<String>,
+    #[pyo3(get)]
+    generator: Option<String>,
+    #[pyo3(get)]
+    build: Option<String>,
+    #[pyo3(get)]
+    seed: Option<u64>,
+    #[pyo3(get)]
+    team: Option<u32>,
+    #[pyo3(get)]
+    hash_id: Option<String>,
+    #[pyo3(get)]
+    permalink: Option<String>,
+    #[pyo3(get)]
+    stats: Stats,
+    #[pyo3(get)]
+    equipment: Equipment,
+    #[pyo3(get)]
+    dungeons: Vec<Dungeon>,
+}
+
+impl Sram {
+    fn parse(sram: &[u8], validate: bool, templates: Option<Templates>) -> PyResult<Self> {
+        if validate {
+            z3r_sramr::validate_sram(sram).map_err(parse_error)?;
         }
+        let mut stats_map = read_stats(sram, false).map_err(parse_error)?;
+        let equip_map = read_equipment(sram, false).map_err(parse_error)?;
+
+        let mut meta = |k: &str| match stats_map.remove(k) {
+            Some(Z3RStat::Meta(m)) => m,
+            Some(stat) => Some(stat.to_string()),
+            None => None,
+        };
+        let (filename, rom_name, generator) =
+            (meta("filename"), meta("rom name"), meta("generator"));
+        let (build, hash_id) = (meta("build"), meta("hash id"));
+        // The seed and team are numbers in `RomInfo`, only the dicts have
+        // them as text
+        stats_map.remove("seed");
+        stats_map.remove("team");
+        let rom = read_rom_info(sram, false).ok();
+        let (seed, team) = (
+            rom.as_ref().and_then(|r| r.seed),
+            rom.as_ref().and_then(|r| r.team),
+        );
+        let links = permalinks(templates.as_ref())?;
+        let permalink = rom.and_then(|rom| links.permalink(&rom));
+        let dungeons = DUNGEONS
+            .iter()
+            .filter_map(|d| Dungeon::from_stat(d, stats_map.get(d)?))
+            .collect();
+
+        Ok(Sram {
+            raw: sram.to_vec(),
+            templates,
+            filename,
+            rom_name,
+            generator,
+            build,
+            seed,
+            team,
+            hash_id,
+            permalink,
+            stats: Stats::from_map(stats_map),
+            equipment: Equipment::from_map(equip_map),
+            dungeons,
+        })
     }
 
-    let mut stats_map: HashMap<&str, Z3RStat> = match read_stats(sram, false) {
-        Ok(map) => map,
-        Err(e) => return Err(ParseException::py_err(format!("{}", e))),
-    };
-    let mut equip_map: HashMap<&str, Z3REquip> = match read_equipment(sram, false) {
-        Ok(map) => map,
-        Err(e) => return Err(ParseException::py_err(format!("{}", e))),
-    };
-
-    let meta_map_py = PyDict::new(py);
-    meta_map_py.set_item(
-        "filename",
-        Z3RStatPy::from(stats_map.remove("filename").unwrap()),
-    )?;
-    meta_map_py.set_item(
-        "hash id",
-        Z3RStatPy::from(stats_map.remove("hash id").unwrap()),
-    )?;
-    meta_map_py.set_item(
-        "permalink",
-        Z3RStatPy::from(stats_map.remove("permalink").unwrap()),
-    )?;
-    let stats_map_py = PyDict::new(py);
-    for (k, v) in stats_map.drain() {
-        stats_map_py.set_item(k, Z3RStatPy::from(v))?;
-    }
-    let equip_map_py = get_equip_map(py, &mut equip_map)?;
-
-    let sram_map = PyDict::new(py);
-    sram_map.set_item("meta", meta_map_py)?;
-    sram_map.set_item("stats", stats_map_py)?;
-    sram_map.set_item("equipment", equip_map_py)?;
-
-    Ok(sram_map)
-}
-
-#[pyfunction(attr_name = "validate_sram")]
-fn validate_sram(sram: &[u8]) -> bool {
-    match z3r_sramr::validate_sram(sram) {
-        Ok(()) => true,
-        Err(_) => false,
-    }
-}
-
-fn get_equip_map<'a>(
-    py: Python<'a>,
-    rs_map: &mut HashMap<&str, Z3REquip>,
-) -> Result<&'a PyDict, PyErr> {
-    let py_map = PyDict::new(py);
-    py_map.set_item(
-        "mirror",
-        equipment::map_mirror(rs_map.remove("mirror").unwrap().value()),
-    )?;
-    py_map.set_item(
-        "sword",
-        equipment::map_sword(rs_map.remove("sword").unwrap().value()),
-    )?;
-    py_map.set_item(
-        "shield",
-        equipment::map_shield(rs_map.remove("shield").unwrap().value()),
-    )?;
-    py_map.set_item(
-        "mail",
-        equipment::map_mail(rs_map.remove("mail").unwrap().value()),
-    )?;
-    py_map.set_item(
-        "gloves",
-        equipment::map_gloves(rs_map.remove("gloves").unwrap().value()),
-    )?;
-    py_map.set_item(
-        "magic consumption",
-        equipment::map_magic_consumption(rs_map.remove("magic consumption").unwrap().value()),
-    )?;
-    py_map.set_item(
-        "bottle 1",
-        equipment::map_bottle_contents(rs_map.remove("bottle 1").unwrap().value()),
-    )?;
-    py_map.set_item(
-        "bottle 2",
-        equipment::map_bottle_contents(rs_map.remove("bottle 2").unwrap().value()),
-    )?;
-    py_map.set_item(
-        "bottle 3",
-        equipment::map_bottle_contents(rs_map.remove("bottle 3").unwrap().value()),
-    )?;
-    py_map.set_item(
-        "bottle 4",
-        equipment::map_bottle_contents(rs_map.remove("bottle 4").unwrap().value()),
-    )?;
-    py_map.set_item(
-        "follower",
-        equipment::map_follower(rs_map.remove("follower").unwrap().value()),
-    )?;
-    for (k, v) in rs_map.drain() {
-        py_map.set_item(k, Z3REquipPy::from(v))?;
-    }
-
-    Ok(py_map)
-}
-
-create_exception!(z3rsramr, ParseException, pyo3::exceptions::Exception);
-
-enum Z3RStatPy {
-    Meta(Option<String>),
-    Number(u32),
-    Fraction(String),
-    Time(String),
-}
-
-enum Z3REquipPy {
-    Has(bool),
-    Number(u32),
-}
-
-impl From<Z3RStat> for Z3RStatPy {
+    fn meta(&self) -> [(&'static str, Option<String>); 8] {
+        [
+            ("filename", self.filename.clone()),
+            ("rom name", self.rom_name.clone()),
+            ("generator", self.generator.clone()),
+            ("build", self.build.clone()),
+            ("seed", self.seed.map(|s| s.to_string())),
+            ("team", self.team.map(|t| t.to_string())),
+            ("hash id", self.hash_id.clone()),
+            ("permalink", self.permalink.clone()),
+        ]
+    }
+}
+
+impl PartialEq for Sram {
+    // Files that only differ in bytes nothing is parsed from are equal
+    fn eq(&self, other: &Self) -> bool {
+        self.meta() == other.meta()
+            && self.stats == other.stats
+            && self.equipment == other.equipment
+    }
+}
+
+#[pymethods]
+impl Sram {
+    #[new]
+    #[pyo3(signature = (sram, validate = true, permalinks = None))]
+    fn new(sram: &[u8], validate: bool, permalinks: Option<Templates>) -> PyResult<Self> {
+        Self::parse(sram, validate, permalinks)
+    }
+
+    /// The `meta`, `stats` and `equipment` dict `parse_sram` returns.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let meta = PyDict::new(py);
+        for (k, v) in self.meta().iter() {
+            meta.set_item(k, v)?;
+        }
+        let sram_map = PyDict::new(py);
+        sram_map.set_item("meta", meta)?;
+        sram_map.set_item("stats", self.stats.to_dict(py)?)?;
+        sram_map.set_item("equipment", self.equipment.to_dict(py)?)?;
+
+        Ok(sram_map)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "Sram(filename={}, rom_name={}, hash_id={})",
+            self.filename.as_deref().into_pyobject(py)?.repr()?,
+            self.rom_name.as_deref().into_pyobject(py)?.repr()?,
+            self.hash_id.as_deref().into_pyobject(py)?.repr()?,
+        ))
+    }
+
+    // The file was already validated, if it was going to be, when it was
+    // first parsed
+    fn __getnewargs__<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> (Bound<'py, PyBytes>, bool, Option<Templates>) {
+        (PyBytes::new(py, &self.raw), false, self.templates.clone())
+    }
+}
+
+/// A stat or equipment value as it appears in the dicts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Value {
+    None,
+    Bool(bool),
+    Int(u32),
+    Str(String),
+}
+
+impl From<Z3RStat> for Value {
     fn from(stat: Z3RStat) -> Self {
         match stat {
-            Z3RStat::Meta(m) => Self::Meta(m),
-            Z3RStat::Number(n) => Self::Number(n),
-            Z3RStat::Fraction(f) => Self::Fraction(f),
-            Z3RStat::Time(t) => Self::Time(t),
+            Z3RStat::Meta(None) => Self::None,
+            Z3RStat::Meta(Some(s)) | Z3RStat::Fraction(s) | Z3RStat::Time(s) => Self::Str(s),
+            Z3RStat::Number(n) => Self::Int(n),
         }
     }
 }
 
-impl From<Z3REquip> for Z3REquipPy {
-    fn from(stat: Z3REquip) -> Self {
-        match stat {
-            Z3REquip::Has(b) => Self::Has(b),
-            Z3REquip::Number(n) => Self::Number(n),
+impl From<Z3REquip> for Value {
+    fn from(equip: Z3REquip) -> Self {
+        match equip {
+            Z3REquip::Has(b) => Self::Bool(b),
+            Z3REquip::Number(n) => Self::Int(n),
         }
     }
 }
 
-impl ToPyObject for Z3RStatPy {
-    fn to_object(&self, py: Python) -> PyObject {
-        match self {
-            Z3RStatPy::Meta(m) => m.to_object(py),
-            Z3RStatPy::Number(n) => n.to_object(py),
-            Z3RStatPy::Fraction(f) => f.to_object(py),
-            Z3RStatPy::Time(t) => t.to_object(py),
+impl From<DisplayValue> for Value {
+    fn from(value: DisplayValue) -> Self {
+        match value {
+            DisplayValue::Value(equip) => equip.into(),
+            DisplayValue::Name(name) => name.map_or(Self::None, Self::Str),
+        }
+    }
+}
+
+impl<'py> IntoPyObject<'py> for &Value {
+    type Target = PyAny;
+    type Output = Bound<'py, PyAny>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> PyResult<Self::Output> {
+        Ok(match self {
+            Value::None => py.None().into_bound(py),
+            Value::Bool(b) => b.into_pyobject(py)?.to_owned().into_any(),
+            Value::Int(n) => n.into_pyobject(py)?.into_any(),
+            Value::Str(s) => PyString::new(py, s).into_any(),
+        })
+    }
+}
+
+impl<'py> FromPyObject<'py> for Value {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        // bool is checked first since Python bools are also ints
+        if ob.is_none() {
+            Ok(Self::None)
+        } else if let Ok(b) = ob.downcast::<PyBool>() {
+            Ok(Self::Bool(b.is_true()))
+        } else if let Ok(s) = ob.downcast::<PyString>() {
+            Ok(Self::Str(s.to_str()?.to_string()))
+        } else {
+            Ok(Self::Int(ob.extract()?))
         }
     }
 }
 
-impl ToPyObject for Z3REquipPy {
-    fn to_object(&self, py: Python) -> PyObject {
-        match self {
-            Z3REquipPy::Has(b) => b.to_object(py),
-            Z3REquipPy::Number(n) => n.to_object(py),
+/// Named values read with the dict keys' spaces replaced by underscores, so
+/// `"small keys"` is `stats.small_keys`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Fields(Vec<(String, Value)>);
+
+impl Fields {
+    fn from_map<V: Into<Value>>(map: Map<&str, V>) -> Self {
+        let mut fields: Vec<(String, Value)> = map
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.into()))
+            .collect();
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Fields(fields)
+    }
+
+    // Sorted like `from_map` so equality doesn't depend on the dict's order
+    fn from_dict(values: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let mut fields = values
+            .iter()
+            .map(|(k, v)| Ok((k.extract()?, v.extract()?)))
+            .collect::<PyResult<Vec<(String, Value)>>>()?;
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(Fields(fields))
+    }
+
+    fn get<'py>(&self, py: Python<'py>, attr: &str) -> PyResult<Bound<'py, PyAny>> {
+        match self.0.iter().find(|(k, _)| k.replace(' ', "_") == attr) {
+            Some((_, v)) => v.into_pyobject(py),
+            None => Err(PyAttributeError::new_err(attr.to_string())),
         }
     }
+
+    fn attributes(&self) -> Vec<String> {
+        self.0.iter().map(|(k, _)| k.replace(' ', "_")).collect()
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for (k, v) in self.0.iter() {
+            dict.set_item(k, v)?;
+        }
+
+        Ok(dict)
+    }
+
+    fn repr(&self, py: Python<'_>, class: &str) -> PyResult<String> {
+        let fields = self
+            .0
+            .iter()
+            .map(|(k, v)| {
+                Ok(format!(
+                    "{}={}",
+                    k.replace(' ', "_"),
+                    v.into_pyobject(py)?.repr()?
+                ))
+            })
+            .collect::<PyResult<Vec<String>>>()?;
+
+        Ok(format!("{}({})", class, fields.join(", ")))
+    }
+}
+
+/// Every stat except the meta values. Fractions and times are strings.
+#[pyclass(module = "z3rsramr", frozen, eq)]
+#[derive(Clone, PartialEq)]
+struct Stats {
+    fields: Fields,
+}
+
+impl Stats {
+    fn from_map(map: Map<&str, Z3RStat>) -> Self {
+        Stats {
+            fields: Fields::from_map(map),
+        }
+    }
+}
+
+#[pymethods]
+impl Stats {
+    /// Builds stats from a dict like `to_dict()` returns.
+    #[new]
+    fn new(values: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Stats {
+            fields: Fields::from_dict(values)?,
+        })
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        self.fields.to_dict(py)
+    }
+
+    fn __getattr__<'py>(&self, py: Python<'py>, name: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.fields.get(py, name)
+    }
+
+    fn __dir__(&self) -> Vec<String> {
+        let mut names = self.fields.attributes();
+        names.push(String::from("to_dict"));
+        names
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        self.fields.repr(py, "Stats")
+    }
+
+    fn __getnewargs__<'py>(&self, py: Python<'py>) -> PyResult<(Bound<'py, PyDict>,)> {
+        Ok((self.to_dict(py)?,))
+    }
+}
+
+/// Every equipment value. Items you either have or don't are bools and
+/// upgrades such as the sword are named, or None when not found.
+#[pyclass(module = "z3rsramr", frozen, eq)]
+#[derive(Clone, PartialEq)]
+struct Equipment {
+    fields: Fields,
+}
+
+impl Equipment {
+    fn from_map(map: Map<&str, Z3REquip>) -> Self {
+        let named = map.into_iter().map(|(k, v)| (k, display_value(k, v)));
+
+        Equipment {
+            fields: Fields::from_map(named.collect::<Map<&str, DisplayValue>>()),
+        }
+    }
+}
+
+#[pymethods]
+impl Equipment {
+    /// Builds equipment from a dict like `to_dict()` returns.
+    #[new]
+    fn new(values: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Equipment {
+            fields: Fields::from_dict(values)?,
+        })
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        self.fields.to_dict(py)
+    }
+
+    fn __getattr__<'py>(&self, py: Python<'py>, name: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.fields.get(py, name)
+    }
+
+    fn __dir__(&self) -> Vec<String> {
+        let mut names = self.fields.attributes();
+        names.push(String::from("to_dict"));
+        names
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        self.fields.repr(py, "Equipment")
+    }
+
+    fn __getnewargs__<'py>(&self, py: Python<'py>) -> PyResult<(Bound<'py, PyDict>,)> {
+        Ok((self.to_dict(py)?,))
+    }
+}
+
+/// Locations checked in one dungeon, out of the locations it has.
+#[pyclass(module = "z3rsramr", frozen, eq, get_all)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Dungeon {
+    name: String,
+    checked: u32,
+    total: u32,
+}
+
+impl Dungeon {
+    fn from_stat(name: &str, stat: &Z3RStat) -> Option<Self> {
+        let (checked, total) = match stat {
+            Z3RStat::Fraction(f) => f.split_once('/')?,
+            _ => return None,
+        };
+
+        Some(Dungeon {
+            name: name.to_string(),
+            checked: checked.parse().ok()?,
+            total: total.parse().ok()?,
+        })
+    }
+}
+
+#[pymethods]
+impl Dungeon {
+    #[new]
+    fn new(name: String, checked: u32, total: u32) -> Self {
+        Dungeon {
+            name,
+            checked,
+            total,
+        }
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("name", &self.name)?;
+        dict.set_item("checked", self.checked)?;
+        dict.set_item("total", self.total)?;
+
+        Ok(dict)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "Dungeon(name={}, checked={}, total={})",
+            PyString::new(py, &self.name).repr()?,
+            self.checked,
+            self.total
+        ))
+    }
+
+    fn __getnewargs__(&self) -> (String, u32, u32) {
+        (self.name.clone(), self.checked, self.total)
+    }
 }
@@ -0,0 +1,177 @@
+from typing import Dict, List, Optional, Union
+
+_Value = Union[None, bool, int, str]
+
+class ParseException(Exception): ...
+
+class InvalidSizeError(ParseException):
+    """The file isn't 32768 bytes."""
+
+class InvalidFileError(ParseException):
+    """The checksum validity word or the randomizer's file marker is wrong."""
+
+class RomNameError(ParseException):
+    """The ROM name doesn't start with VT or ER."""
+
+class ChecksumError(ParseException):
+    """The stored inverse checksum doesn't match the file."""
+
+def validate_sram(sram: bytes) -> bool: ...
+def parse_sram(
+    sram: bytes,
+    validate: bool = True,
+    permalinks: Optional[Dict[str, Optional[str]]] = None,
+) -> Dict[str, Dict[str, _Value]]: ...
+
+class Stats:
+    a_items: str
+    big_chests: str
+    big_keys: str
+    bonks: int
+    boots_found: str
+    capacity_upgrades: str
+    castle_tower: str
+    chest_locations: int
+    collection_rate: str
+    compasses: str
+    crystals: str
+    deaths: int
+    desert_palace: str
+    eastern_palace: str
+    faerie_revivals: int
+    fighter_sword_bosses: str
+    first_sword: str
+    flute_found: str
+    ganons_tower: str
+    ganons_tower_big_key: str
+    golden_sword_bosses: str
+    heart_containers: str
+    heart_pieces: str
+    hyrule_castle: str
+    ice_palace: str
+    lag_time: str
+    locations_pre_boots: int
+    locations_pre_mirror: int
+    mails: str
+    maps: str
+    master_sword_bosses: str
+    menu_time: str
+    mirror_found: str
+    misery_mire: str
+    other_locations: int
+    overworld_mirrors: int
+    palace_of_darkness: str
+    pendants: str
+    rupees_spent: int
+    save_and_quits: int
+    screen_transitions: int
+    shields: str
+    skull_woods: str
+    small_keys: str
+    swamp_palace: str
+    swordless_bosses: str
+    swords: str
+    tempered_sword_bosses: str
+    thieves_town: str
+    times_fluted: int
+    total_time: str
+    tower_of_hera: str
+    turtle_rock: str
+    underworld_mirrors: int
+    y_items: str
+    def __init__(self, values: Dict[str, _Value]) -> None: ...
+    def to_dict(self) -> Dict[str, _Value]: ...
+
+class Equipment:
+    arrow_upgrades: int
+    blue_boomerang: bool
+    blue_pendant: bool
+    bomb_upgrades: int
+    bombos: bool
+    book: bool
+    boots: bool
+    bottle_1: Optional[str]
+    bottle_2: Optional[str]
+    bottle_3: Optional[str]
+    bottle_4: Optional[str]
+    bottles: int
+    bow: bool
+    bug_net: bool
+    byrna: bool
+    cape: bool
+    crystal_1: bool
+    crystal_2: bool
+    crystal_3: bool
+    crystal_4: bool
+    crystal_5: bool
+    crystal_6: bool
+    crystal_7: bool
+    current_arrows: int
+    current_bombs: int
+    current_health: int
+    current_hearts: int
+    current_magic: int
+    current_rupees: int
+    ether: bool
+    fire_rod: bool
+    flippers: bool
+    flute: bool
+    follower: Optional[str]
+    gloves: Optional[str]
+    goal_items: int
+    green_pendant: bool
+    hammer: bool
+    heart_pieces: int
+    held_keys: int
+    hookshot: bool
+    ice_rod: bool
+    lamp: bool
+    magic_consumption: str
+    magic_multiplier: int
+    mail: str
+    max_arrows: int
+    max_bombs: int
+    max_hearts: int
+    mirror: Optional[str]
+    moon_pearl: bool
+    mushroom: bool
+    mushroom_turned_in: bool
+    powder: bool
+    progressive_bows: int
+    quake: bool
+    red_boomerang: bool
+    red_pendant: bool
+    shield: Optional[str]
+    shovel: bool
+    silver_arrows: bool
+    somaria: bool
+    sword: Optional[str]
+    def __init__(self, values: Dict[str, _Value]) -> None: ...
+    def to_dict(self) -> Dict[str, _Value]: ...
+
+class Dungeon:
+    name: str
+    checked: int
+    total: int
+    def __init__(self, name: str, checked: int, total: int) -> None: ...
+    def to_dict(self) -> Dict[str, Union[str, int]]: ...
+
+class Sram:
+    filename: Optional[str]
+    rom_name: Optional[str]
+    generator: Optional[str]
+    build: Optional[str]
+    seed: Optional[int]
+    team: Optional[int]
+    hash_id: Optional[str]
+    permalink: Optional[str]
+    stats: Stats
+    equipment: Equipment
+    dungeons: List[Dungeon]
+    def __init__(
+        self,
+        sram: bytes,
+        validate: bool = True,
+        permalinks: Optional[Dict[str, Optional[str]]] = None,
+    ) -> None: ...
+    def to_dict(self) -> Dict[str, Dict[str, _Value]]: ...
@@ -0,0 +1,137 @@
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::{env, io::Read, process};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use z3r_sramr::{parse_sram, timeline::diff_snapshots, validate_sram};
+
+const USAGE: &str = "\
+Usage:
+    z3r-sramr-server [--address <host:port>]
+
+Serves on 127.0.0.1:8080 by default.
+
+Endpoints:
+    POST /validate    Body is one SRAM file
+    POST /parse       Body is one SRAM file
+    POST /diff        Body is two SRAM files back to back, before then after
+
+/parse and /diff validate their input unless called with ?validate=false.";
+
+const SRAM_SIZE: usize = 32768;
+
+// Two files for /diff, plus a byte to tell an oversized body from a full one
+const MAX_BODY: u64 = 2 * SRAM_SIZE as u64 + 1;
+
+fn respond(request: Request, status: u16, body: Value) {
+    let header =
+        Header::from_bytes("Content-Type", "application/json").expect("static header is valid");
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(header);
+    if let Err(e) = request.respond(response) {
+        eprintln!("Failed to send response: {}", e);
+    }
+}
+
+fn error(message: impl ToString) -> Value {
+    json!({ "error": message.to_string() })
+}
+
+fn read_body(request: &mut Request) -> Result<Vec<u8>> {
+    let mut body: Vec<u8> = Vec::with_capacity(SRAM_SIZE);
+    request.as_reader().take(MAX_BODY).read_to_end(&mut body)?;
+
+    Ok(body)
+}
+
+fn handle(request: &mut Request) -> (u16, Value) {
+    let (path, query) = match request.url().split_once('?') {
+        Some((path, query)) => (path.to_string(), query.to_string()),
+        None => (request.url().to_string(), String::new()),
+    };
+    let validate = !query.split('&').any(|p| p == "validate=false");
+
+    if !["/validate", "/parse", "/diff"].contains(&path.as_str()) {
+        return (404, error("Not found"));
+    }
+    if *request.method() != Method::Post {
+        return (405, error("Use POST"));
+    }
+    let body = match read_body(request) {
+        Ok(body) if body.len() as u64 >= MAX_BODY => return (413, error("Body too large")),
+        Ok(body) => body,
+        Err(e) => return (400, error(e)),
+    };
+
+    match path.as_str() {
+        "/validate" => match validate_sram(&body) {
+            Ok(()) => (200, json!({ "valid": true })),
+            Err(e) => (200, json!({ "valid": false, "error": e.to_string() })),
+        },
+        "/parse" => match parse_sram(&body, validate)
+            .map_err(anyhow::Error::from)
+            .and_then(|p| Ok(serde_json::to_value(p)?))
+        {
+            Ok(parsed) => (200, parsed),
+            Err(e) => (422, error(e)),
+        },
+        _ => match diff(&body, validate) {
+            Ok(events) => (200, events),
+            Err(e) => (422, error(e)),
+        },
+    }
+}
+
+fn diff(body: &[u8], validate: bool) -> Result<Value> {
+    if body.len() != 2 * SRAM_SIZE {
+        return Err(anyhow!(
+            "Expected two {} byte files, got {} bytes",
+            SRAM_SIZE,
+            body.len()
+        ));
+    }
+    let (before, after) = body.split_at(SRAM_SIZE);
+    let events = diff_snapshots(
+        &parse_sram(before, validate)?,
+        &parse_sram(after, validate)?,
+    );
+
+    Ok(json!({ "events": events }))
+}
+
+fn run() -> Result<()> {
+    let mut address = String::from("127.0.0.1:8080");
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--address" => {
+                address = args
+                    .next()
+                    .ok_or_else(|| anyhow!("Missing value for {}", arg))?
+            }
+            "help" | "--help" | "-h" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ => return Err(anyhow!("Unknown option {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    let server =
+        Server::http(&address).map_err(|e| anyhow!("Couldn't listen on {}: {}", address, e))?;
+    println!("Listening on {}", address);
+    for mut request in server.incoming_requests() {
+        let (status, body) = handle(&mut request);
+        respond(request, status, body);
+    }
+
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
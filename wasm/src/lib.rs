@@ -0,0 +1,77 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use z3r_sramr::{
+    equipment::{display_value, read_equipment, DisplayValue},
+    rom::{read_rom_info, Permalinks},
+    stats::{read_stats, Z3RStat, META_STATS},
+    Map,
+};
+
+/// The same `meta`, `stats` and `equipment` layout the Python bindings
+/// return, so pages and scripts can share code.
+#[derive(Serialize)]
+struct ParsedJs {
+    meta: Map<&'static str, Z3RStat>,
+    stats: Map<&'static str, Z3RStat>,
+    equipment: Map<&'static str, DisplayValue>,
+}
+
+#[wasm_bindgen(js_name = validateSram)]
+pub fn validate_sram(sram: &[u8]) -> bool {
+    z3r_sramr::validate_sram(sram).is_ok()
+}
+
+/// Parses a file into a plain object. Validates first unless `validate` is
+/// `false` and throws with the validation or parse error on failure.
+///
+/// `permalinks` optionally maps a ROM title prefix, `"VT"` or `"ER"`, to a
+/// permalink template such as `"https://example.com/h/{hash}"`, or to `null`
+/// for no permalink. Other generators keep the default.
+#[wasm_bindgen(js_name = parseSram)]
+pub fn parse_sram(
+    sram: &[u8],
+    validate: Option<bool>,
+    permalinks: JsValue,
+) -> Result<JsValue, JsError> {
+    let templates: Option<Map<String, Option<String>>> =
+        serde_wasm_bindgen::from_value(permalinks)?;
+    let mut links = Permalinks::default();
+    for (prefix, template) in templates.iter().flatten() {
+        links = links
+            .with_prefix(prefix, template.as_deref())
+            .map_err(|e| JsError::new(&e.to_string()))?;
+    }
+    let parsed = parse(sram, validate.unwrap_or(true), &links).map_err(|e| JsError::new(&e))?;
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+
+    Ok(parsed.serialize(&serializer)?)
+}
+
+fn parse(sram: &[u8], validate: bool, links: &Permalinks) -> Result<ParsedJs, String> {
+    if validate {
+        z3r_sramr::validate_sram(sram).map_err(|e| e.to_string())?;
+    }
+    let mut stats = read_stats(sram, false).map_err(|e| e.to_string())?;
+    let equip = read_equipment(sram, false).map_err(|e| e.to_string())?;
+
+    let mut meta: Map<&'static str, Z3RStat> = Map::new();
+    for k in META_STATS.iter() {
+        if let Some(v) = stats.remove(k) {
+            meta.insert(*k, v);
+        }
+    }
+    let permalink = read_rom_info(sram, false)
+        .ok()
+        .and_then(|rom| links.permalink(&rom));
+    meta.insert("permalink", Z3RStat::Meta(permalink));
+
+    Ok(ParsedJs {
+        meta,
+        stats,
+        equipment: equip
+            .into_iter()
+            .map(|(k, v)| (k, display_value(k, v)))
+            .collect(),
+    })
+}